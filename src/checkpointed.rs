@@ -0,0 +1,293 @@
+#[cfg(feature = "allocator_api")]
+use std::alloc::{Allocator, Global};
+use std::{borrow::Cow, ops::Range};
+
+use crate::{
+    delta::{Delta, DeltaHistory},
+    PersistentString, RedoError, UndoError, VersionIntegrityError, VersionSwitchError,
+};
+
+/// Number of deltas between checkpoints used by default.
+const DEFAULT_CHECKPOINT_INTERVAL: usize = 16;
+
+/// [`PersistentString`] which stores deltas producing the resulting string
+/// along with the full content of every `checkpoint_interval`-th version,
+/// so that generating any version replays less than `checkpoint_interval` deltas.
+#[cfg(feature = "allocator_api")]
+#[derive(Clone, Debug)]
+pub struct CheckpointedDeltaString<A: Allocator = Global> {
+    history: DeltaHistory<A>,
+}
+#[cfg(not(feature = "allocator_api"))]
+#[derive(Clone, Debug)]
+pub struct CheckpointedDeltaString {
+    history: DeltaHistory,
+}
+
+// Manual implementation is used instead of derive to allow specifying custom allocator
+impl Default for CheckpointedDeltaString {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<A: Allocator + Clone> CheckpointedDeltaString<A> {
+    /// Creates an empty string storing its deltas and checkpoints in `allocator`.
+    ///
+    /// The contents of the checkpoints and the strings held by the deltas
+    /// are allocated by the global allocator as [`String`] does not support custom allocators.
+    #[cfg(feature = "allocator_api")]
+    pub fn new_in(allocator: A) -> Self {
+        Self {
+            history: DeltaHistory::new_in(allocator, Some(DEFAULT_CHECKPOINT_INTERVAL)),
+        }
+    }
+}
+
+impl CheckpointedDeltaString {
+    pub fn new() -> Self {
+        Self::with_checkpoint_interval(DEFAULT_CHECKPOINT_INTERVAL)
+    }
+
+    /// Creates an empty string storing a checkpoint every `checkpoint_interval` deltas.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `checkpoint_interval` is `0`.
+    pub fn with_checkpoint_interval(checkpoint_interval: usize) -> Self {
+        assert!(
+            checkpoint_interval > 0,
+            "checkpoint interval should be positive"
+        );
+
+        Self::from_base(String::new(), checkpoint_interval)
+    }
+
+    /// Reserves capacity for at least `additional` more versions.
+    pub fn reserve_versions(&mut self, additional: usize) {
+        self.history.reserve(additional)
+    }
+
+    /// Shrinks the capacity reserved for versions and checkpoints as much as possible.
+    pub fn shrink_versions_to_fit(&mut self) {
+        self.history.shrink_to_fit()
+    }
+
+    /// Returns the number of deltas between checkpoints.
+    pub fn checkpoint_interval(&self) -> usize {
+        self.history
+            .checkpoint_interval()
+            .expect("checkpoints should always be stored")
+    }
+
+    fn from_base(base: String, checkpoint_interval: usize) -> Self {
+        Self {
+            history: DeltaHistory::from_base(base, Some(checkpoint_interval)),
+        }
+    }
+
+    /// Applies `delta` to the current version producing a new version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the delta's range is out of bounds or does not lie on char boundaries.
+    pub fn apply_delta(&mut self, delta: Delta) {
+        self.history.apply_delta(delta)
+    }
+
+    /// Replaces the content of the given version with `new_content`
+    /// replaying the deltas of all the following versions onto it.
+    ///
+    /// This is expensive as all the deltas are replayed and the checkpoints are rebuilt,
+    /// and it changes the content of all the versions following the rewritten one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some following delta's range does not apply to the rewritten content,
+    /// in which case the history is left unchanged.
+    pub fn rewrite_version(
+        &mut self,
+        version: usize,
+        new_content: &str,
+    ) -> Result<(), VersionSwitchError> {
+        self.history.rewrite_version(version, new_content)
+    }
+}
+
+impl PersistentString for CheckpointedDeltaString {
+    fn from_chunks<'a>(chunks: impl IntoIterator<Item = &'a str>) -> Self {
+        Self::from_base(chunks.into_iter().collect(), DEFAULT_CHECKPOINT_INTERVAL)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.history.len() == 0
+    }
+
+    fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    fn snapshot(&self) -> Cow<'_, str> {
+        self.history.snapshot()
+    }
+
+    fn snapshot_at(&self, version: usize) -> Result<Cow<'_, str>, VersionSwitchError> {
+        self.history.snapshot_at(version)
+    }
+
+    fn push(&mut self, character: char) {
+        self.history.push_delta(Delta::Push(character))
+    }
+
+    fn for_each_version(&self, f: impl FnMut(usize, &str)) {
+        self.history.for_each_version(f)
+    }
+
+    fn to_delta_log(&self) -> Vec<Delta> {
+        self.history.to_delta_log()
+    }
+
+    fn push_str(&mut self, string: &str) {
+        self.history.push_delta(Delta::PushStr(string.to_string()))
+    }
+
+    fn replace_range(&mut self, range: Range<usize>, replace_with: &str) {
+        self.history.apply_delta(Delta::ReplaceRange {
+            range,
+            replace_with: replace_with.to_string(),
+        })
+    }
+
+    fn repeat(&mut self, times: usize) {
+        self.history.push_delta(Delta::Repeat(times))
+    }
+
+    fn latest_version(&self) -> usize {
+        self.history.latest_version()
+    }
+
+    fn version_internal_size(&self, version: usize) -> Result<usize, VersionSwitchError> {
+        self.history.version_internal_size(version)
+    }
+
+    fn validate_versions(&self) -> Result<(), VersionIntegrityError> {
+        self.history.validate()
+    }
+
+    fn collapse_no_op_versions(&mut self) {
+        self.history.collapse_no_op_versions()
+    }
+
+    fn rebase_onto_current(&mut self) {
+        self.history.rebase_onto_current()
+    }
+
+    fn split_history(&self, version: usize) -> Result<(Self, Self), VersionSwitchError> {
+        let (past, future) = self.history.split(version)?;
+
+        Ok((Self { history: past }, Self { history: future }))
+    }
+
+    fn cut_range(&mut self, range: Range<usize>) -> Self {
+        Self {
+            history: self.history.cut_range(range),
+        }
+    }
+
+    fn undo(&mut self) -> Result<(), UndoError> {
+        self.history.undo()
+    }
+
+    fn redo(&mut self) -> Result<(), RedoError> {
+        self.history.redo()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CheckpointedDeltaString;
+    use crate::{delta::Delta, PersistentString};
+
+    crate::tests::persistent_string_test_suite!(CheckpointedDeltaString::with_checkpoint_interval(
+        2
+    ));
+
+    #[test]
+    fn test_checkpoints_bound_replays() {
+        let mut string = CheckpointedDeltaString::with_checkpoint_interval(4);
+        let mut expected = vec![String::new()];
+        for index in 0..19 {
+            match index % 3 {
+                0 => string.push_str("ab"),
+                1 => string.replace_range(0..1, "c"),
+                _ => string.push('d'),
+            }
+            expected.push(string.snapshot().into_owned());
+        }
+        assert_eq!(string.history.checkpoint_count(), 4);
+
+        for (version, expected) in expected.iter().enumerate() {
+            assert_eq!(string.snapshot_at(version).unwrap(), expected.as_str());
+            let (checkpoint_version, _) = string.history.nearest_checkpoint(version);
+            assert!(version - checkpoint_version < 4);
+        }
+
+        assert!(string.undo_n(10).is_ok());
+        assert_eq!(string.snapshot(), expected[9]);
+        string.push('e');
+        assert_eq!(string.history.checkpoint_count(), 2);
+        assert_eq!(string.snapshot(), format!("{}e", expected[9]));
+        assert_eq!(string.validate_versions(), Ok(()));
+    }
+
+    #[test]
+    fn test_cached_len_across_checkpoints() {
+        let mut string = CheckpointedDeltaString::with_checkpoint_interval(2);
+        let mut lens = vec![0];
+        for index in 0..7 {
+            string.push_str(&"a".repeat(index));
+            lens.push(string.len());
+            assert_eq!(string.len(), string.snapshot().len());
+        }
+
+        for version in (0..7).rev() {
+            assert!(string.undo().is_ok());
+            assert_eq!(string.len(), lens[version]);
+        }
+        for len in &lens[1..4] {
+            assert!(string.redo().is_ok());
+            assert_eq!(string.len(), *len);
+        }
+        string.push('b');
+        assert_eq!(string.len(), lens[3] + 1);
+        assert_eq!(string.validate_versions(), Ok(()));
+    }
+
+    #[test]
+    fn test_reserve_versions() {
+        let mut string = CheckpointedDeltaString::new();
+        string.reserve_versions(16);
+        let capacity = string.history.version_capacity();
+        assert!(capacity >= 16);
+
+        for _ in 0..16 {
+            string.push('a');
+        }
+        assert_eq!(string.history.version_capacity(), capacity);
+        assert_eq!(string.snapshot(), "a".repeat(16));
+    }
+
+    #[test]
+    fn test_apply_delta_and_rewrite_version() {
+        let mut string = CheckpointedDeltaString::with_checkpoint_interval(2);
+        string.apply_delta(Delta::PushStr("password=".to_string()));
+        string.apply_delta(Delta::PushStr("hunter2".to_string()));
+        string.apply_delta(Delta::Repeat(2));
+
+        assert!(string.rewrite_version(2, "password=*******").is_ok());
+        assert_eq!(string.snapshot_at(1).unwrap(), "password=");
+        assert_eq!(string.snapshot(), "password=*******".repeat(2));
+        assert_eq!(string.validate_versions(), Ok(()));
+    }
+}
@@ -1,50 +1,91 @@
 #[cfg(feature = "allocator_api")]
 use std::alloc::{Allocator, Global};
-use std::{borrow::Cow, collections::VecDeque};
+use std::{borrow::Cow, collections::VecDeque, ops::Range};
 
-use crate::{PersistentString, RedoError, UndoError};
+use crate::{PersistentString, RedoError, UndoError, VersionIntegrityError, VersionSwitchError};
 
 /// [`PersistentString`] which keeps every reachable version of itself,
 /// cloning current version on each mutation.
 #[cfg(feature = "allocator_api")]
 #[derive(Clone, Debug)]
 pub struct CowPersistentString<A: Allocator = Global> {
-    /// Stack of reachable string versions.
+    /// Content of the initial version.
+    base: String,
+    /// Stack of reachable string versions following the initial one.
     versions: VecDeque<String, A>,
     /// Index of the current version in [`versions`] subtracted by `1`.
-    /// The value of `0` corresponds to an empty state.
+    /// The value of `0` corresponds to the initial version.
     current_version: usize,
 }
 #[cfg(not(feature = "allocator_api"))]
 #[derive(Clone, Debug)]
 pub struct CowPersistentString {
-    /// Stack of reachable string versions.
+    /// Content of the initial version.
+    base: String,
+    /// Stack of reachable string versions following the initial one.
     versions: VecDeque<String>,
     /// Index of the current version in [`versions`] subtracted by `1`.
-    /// The value of `0` corresponds to an empty state.
+    /// The value of `0` corresponds to the initial version.
     current_version: usize,
 }
 
 impl CowPersistentString {
     pub fn new() -> Self {
+        Self::from_base(String::new())
+    }
+
+    /// Reserves capacity for at least `additional` more versions.
+    pub fn reserve_versions(&mut self, additional: usize) {
+        self.versions.reserve(additional)
+    }
+
+    /// Shrinks the capacity reserved for versions as much as possible.
+    pub fn shrink_versions_to_fit(&mut self) {
+        self.versions.shrink_to_fit()
+    }
+
+    fn from_base(base: String) -> Self {
         Self {
+            base,
             versions: VecDeque::new(),
             current_version: 0,
         }
     }
 
-    fn current_version(&self) -> Option<&String> {
+    /// Returns the total length of all stored versions.
+    pub fn total_stored_bytes(&self) -> usize {
+        self.base.len() + self.versions.iter().map(String::len).sum::<usize>()
+    }
+
+    /// Returns the total capacity reserved by all stored versions.
+    pub fn total_reserved_bytes(&self) -> usize {
+        self.base.capacity() + self.versions.iter().map(String::capacity).sum::<usize>()
+    }
+
+    /// Returns the content of the current version as contiguous byte slices
+    /// suitable for [vectored writes](std::io::Write::write_vectored).
+    ///
+    /// As each version is stored as a whole, there is at most one chunk.
+    pub fn chunks(&self) -> Vec<&[u8]> {
+        Some(self.current_version().as_bytes())
+            .filter(|current| !current.is_empty())
+            .into_iter()
+            .collect()
+    }
+
+    fn current_version(&self) -> &String {
         match self.current_version {
-            0 => None,
-            current_version => self.versions.get(current_version - 1),
+            0 => &self.base,
+            current_version => &self.versions[current_version - 1],
         }
     }
 
-    fn mutate_or_else(
-        &mut self,
-        operation: impl FnOnce(&String) -> String,
-        fallback: impl FnOnce() -> String,
-    ) {
+    fn mutate(&mut self, operation: impl FnOnce(&String) -> String) {
+        let version = operation(self.current_version());
+        self.push_version(version);
+    }
+
+    fn push_version(&mut self, version: String) {
         let current_version = self.current_version;
         // there may be later versions from which `undo` happened,
         // these should no longer be reachable
@@ -53,8 +94,7 @@ impl CowPersistentString {
             let popped = self.versions.pop_back();
             debug_assert!(popped.is_some());
         }
-        self.versions
-            .push_back(self.versions.back().map(operation).unwrap_or_else(fallback));
+        self.versions.push_back(version);
 
         self.current_version = current_version + 1;
     }
@@ -69,9 +109,14 @@ impl Default for CowPersistentString {
 
 #[cfg(feature = "allocator_api")]
 impl<A: Allocator> CowPersistentString<A> {
+    /// Creates an empty string storing its stack of versions in `allocator`.
+    ///
+    /// Only the stack itself is allocated there: the content of each version,
+    /// including the initial one, is a [`String`] allocated by the global allocator.
     #[cfg(feature = "allocator_api")]
     pub fn new_in(allocator: A) -> Self {
         Self {
+            base: String::new(),
             versions: VecDeque::new_in(allocator),
             current_version: 0,
         }
@@ -79,37 +124,140 @@ impl<A: Allocator> CowPersistentString<A> {
 }
 
 impl PersistentString for CowPersistentString {
+    fn from_chunks<'a>(chunks: impl IntoIterator<Item = &'a str>) -> Self {
+        Self::from_base(chunks.into_iter().collect())
+    }
+
     fn is_empty(&self) -> bool {
-        self.current_version()
-            .map(|current| current.is_empty())
-            .unwrap_or(true)
+        self.current_version().is_empty()
     }
 
     fn len(&self) -> usize {
-        self.current_version()
-            .map(|current| current.len())
-            .unwrap_or(0)
+        self.current_version().len()
     }
 
-    fn snapshot(&self) -> Cow<str> {
-        self.current_version()
-            .map(|current| Cow::Borrowed(current.as_ref()))
-            .unwrap_or_else(|| Cow::Owned(String::new()))
+    fn snapshot(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.current_version())
+    }
+
+    fn snapshot_at(&self, version: usize) -> Result<Cow<'_, str>, VersionSwitchError> {
+        match version {
+            0 => Ok(Cow::Borrowed(&self.base)),
+            version => self
+                .versions
+                .get(version - 1)
+                .map(|version| Cow::Borrowed(version.as_ref()))
+                .ok_or(VersionSwitchError::NonExistent),
+        }
     }
 
     fn push_str(&mut self, suffix: &str) {
-        self.mutate_or_else(
-            |current| {
-                let mut current = current.clone();
-                current.push_str(suffix);
+        self.mutate(|current| {
+            let mut current = current.clone();
+            current.push_str(suffix);
 
-                current
-            },
-            || suffix.to_string(),
-        )
+            current
+        })
+    }
+
+    fn replace_range(&mut self, range: Range<usize>, replace_with: &str) {
+        let mut version = self.current_version().clone();
+        version.replace_range(range, replace_with);
+        self.push_version(version);
+    }
+
+    fn retain(&mut self, mut filter: impl FnMut(char) -> bool)
+    where
+        Self: Sized,
+    {
+        // the new version is filtered directly from the current one without cloning it first
+        self.mutate(|current| {
+            let mut retained = String::with_capacity(current.len());
+            retained.extend(current.chars().filter(|&character| filter(character)));
+
+            retained
+        })
     }
+
     fn repeat(&mut self, times: usize) {
-        self.mutate_or_else(|current| current.repeat(times), || String::new())
+        self.mutate(|current| {
+            if current.is_empty() || times == 0 {
+                String::new()
+            } else {
+                current.repeat(times)
+            }
+        })
+    }
+
+    fn latest_version(&self) -> usize {
+        self.versions.len()
+    }
+
+    fn version_internal_size(&self, version: usize) -> Result<usize, VersionSwitchError> {
+        let version = match version {
+            0 => &self.base,
+            version => self
+                .versions
+                .get(version - 1)
+                .ok_or(VersionSwitchError::NonExistent)?,
+        };
+
+        Ok(size_of::<String>() + version.capacity())
+    }
+
+    fn validate_versions(&self) -> Result<(), VersionIntegrityError> {
+        if self.current_version <= self.versions.len() {
+            Ok(())
+        } else {
+            Err(VersionIntegrityError::CurrentOutOfRange)
+        }
+    }
+
+    fn collapse_no_op_versions(&mut self) {
+        let versions = std::mem::take(&mut self.versions);
+        let mut current_version = 0;
+        for (index, version) in versions.into_iter().enumerate() {
+            if version != *self.versions.back().unwrap_or(&self.base) {
+                self.versions.push_back(version);
+            }
+            if index + 1 == self.current_version {
+                current_version = self.versions.len();
+            }
+        }
+
+        self.current_version = current_version;
+    }
+
+    fn rebase_onto_current(&mut self) {
+        if let Some(base) = self.versions.drain(..self.current_version).next_back() {
+            self.base = base;
+        }
+
+        self.current_version = 0;
+    }
+
+    fn split_history(&self, version: usize) -> Result<(Self, Self), VersionSwitchError> {
+        let future_base = self.snapshot_at(version)?.into_owned();
+
+        Ok((
+            Self {
+                base: self.base.clone(),
+                versions: self.versions.iter().take(version).cloned().collect(),
+                current_version: self.current_version.min(version),
+            },
+            Self {
+                base: future_base,
+                versions: self.versions.iter().skip(version).cloned().collect(),
+                current_version: self.current_version.saturating_sub(version),
+            },
+        ))
+    }
+
+    fn cut_range(&mut self, range: Range<usize>) -> Self {
+        let cut = self.snapshot()[range.clone()].to_string();
+        self.replace_range(range, "");
+
+        Self::from_base(cut)
     }
 
     fn undo(&mut self) -> Result<(), UndoError> {
@@ -138,5 +286,148 @@ impl PersistentString for CowPersistentString {
 
 #[cfg(test)]
 mod tests {
-    crate::tests::persistent_string_test_suite!(super::CowPersistentString::new());
+    use super::CowPersistentString;
+    use crate::{PersistentString, VersionIntegrityError, VersionSwitchError};
+    use std::{
+        borrow::Cow,
+        io::{IoSlice, Write},
+    };
+
+    crate::tests::persistent_string_test_suite!(CowPersistentString::new());
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn test_new_in_stores_versions_in_allocator() {
+        let allocator = crate::tests::CountingAllocator::default();
+        let mut string = CowPersistentString::new_in(&allocator);
+        string.versions.reserve(4);
+
+        assert_eq!(allocator.allocations(), 1);
+    }
+
+    #[test]
+    fn test_reserve_versions() {
+        let mut string = CowPersistentString::new();
+        string.reserve_versions(16);
+        let capacity = string.versions.capacity();
+        assert!(capacity >= 16);
+
+        for _ in 0..16 {
+            string.push('a');
+        }
+        assert_eq!(string.versions.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_total_bytes() {
+        let mut string = CowPersistentString::new();
+        assert_eq!(string.total_stored_bytes(), 0);
+
+        string.push_str("foo");
+        string.repeat(3);
+        string.push_str("bar");
+        assert!(string.undo().is_ok());
+
+        let snapshots_len = (0..=string.latest_version())
+            .map(|version| string.snapshot_at(version).unwrap().len())
+            .sum::<usize>();
+        assert_eq!(string.total_stored_bytes(), snapshots_len);
+        assert!(string.total_reserved_bytes() >= snapshots_len);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut string = CowPersistentString::new();
+        assert!(string.chunks().is_empty());
+
+        string.push_str("foo");
+        string.push_str("bar");
+        let slices = string
+            .chunks()
+            .into_iter()
+            .map(IoSlice::new)
+            .collect::<Vec<_>>();
+        let mut written = Vec::new();
+        assert_eq!(written.write_vectored(&slices).unwrap(), 6);
+        assert_eq!(written, string.snapshot().as_bytes());
+    }
+
+    #[test]
+    fn test_byte_slice_at_borrows() {
+        let mut string = CowPersistentString::new();
+        string.push_str("foo");
+        string.push_str("bar");
+
+        assert!(matches!(
+            string.byte_slice_at(2, 1..4),
+            Some(Cow::Borrowed(b"oob"))
+        ));
+    }
+
+    #[test]
+    fn test_nth_line_borrows() {
+        let mut string = CowPersistentString::new();
+        string.push_str("foo\nbar");
+
+        assert!(matches!(string.nth_line(1), Some(Cow::Borrowed("bar"))));
+    }
+
+    #[test]
+    fn test_validate_versions_detects_current_out_of_range() {
+        let mut string = CowPersistentString::new();
+        string.push_str("foo");
+        assert_eq!(string.validate_versions(), Ok(()));
+
+        string.current_version = 2;
+        assert_eq!(
+            string.validate_versions(),
+            Err(VersionIntegrityError::CurrentOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_retain_matches_string_retain() {
+        let content = "a1b2 ç3đ4 🙂5";
+        let mut string = CowPersistentString::new();
+        string.push_str(content);
+
+        string.retain(|character| !character.is_ascii_digit());
+        let mut expected = content.to_string();
+        expected.retain(|character| !character.is_ascii_digit());
+        assert_eq!(string.snapshot(), expected);
+        assert_eq!(string.snapshot_at(1).unwrap(), content);
+
+        string.retain(|_| true);
+        assert_eq!(string.snapshot(), expected);
+        assert_eq!(string.latest_version(), 3);
+    }
+
+    #[test]
+    fn test_shrink_versions_to_fit() {
+        let mut string = CowPersistentString::new();
+        for _ in 0..64 {
+            string.push('a');
+        }
+        let capacity = string.versions.capacity();
+
+        string.rebase_onto_current();
+        string.push('b');
+        string.shrink_versions_to_fit();
+        assert!(string.versions.capacity() < capacity);
+        assert_eq!(string.snapshot(), format!("{}b", "a".repeat(64)));
+    }
+
+    #[test]
+    fn test_version_internal_size_of_append() {
+        let mut string = CowPersistentString::new();
+        string.push_str(&"a".repeat(1024));
+        string.push('b');
+
+        assert!(string.version_internal_size(1).unwrap() >= 1024);
+        assert!(string.version_internal_size(2).unwrap() >= 1025);
+        assert_eq!(
+            string.version_internal_size(3),
+            Err(VersionSwitchError::NonExistent)
+        );
+    }
 }
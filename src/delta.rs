@@ -1,140 +1,651 @@
 #[cfg(feature = "allocator_api")]
 use std::alloc::{Allocator, Global};
-use std::{borrow::Cow, collections::VecDeque};
+use std::{borrow::Cow, collections::VecDeque, ops::Range};
 
-use crate::{PersistentString, RedoError, UndoError};
+use crate::{PersistentString, RedoError, UndoError, VersionIntegrityError, VersionSwitchError};
 
-/// [`PersistentString`] which only stores deltas producing the resulting string.#[cfg(feature = "allocator_api")]
+/// [`PersistentString`] which only stores deltas producing the resulting string.
 #[cfg(feature = "allocator_api")]
 #[derive(Clone, Debug)]
 pub struct DeltaPersistentString<A: Allocator = Global> {
-    /// Sequence of operations producing current string.
-    deltas: VecDeque<Delta, A>,
-    /// Index of the current version in [`versions`] subtracted by `1`.
-    /// The value of `0` corresponds to an empty state.
-    current_version: usize,
+    history: DeltaHistory<A>,
 }
 #[cfg(not(feature = "allocator_api"))]
 #[derive(Clone, Debug)]
 pub struct DeltaPersistentString {
-    /// Sequence of operations producing current string.
-    deltas: VecDeque<Delta>,
-    /// Index of the current version in [`versions`] subtracted by `1`.
-    /// The value of `0` corresponds to an empty state.
-    current_version: usize,
+    history: DeltaHistory,
 }
 
 /// Operations mutating the string.
 #[derive(Clone, Debug, Eq, PartialEq)]
-enum Delta {
+pub enum Delta {
+    /// Appends the character.
+    Push(char),
+    /// Appends the string.
     PushStr(String),
+    /// Repeats the whole string the given number of times.
     Repeat(usize),
+    /// Replaces the bytes within the range with the string.
+    ReplaceRange {
+        range: Range<usize>,
+        replace_with: String,
+    },
 }
 
 impl Delta {
+    fn is_applicable_to(&self, string: &str) -> bool {
+        match self {
+            Self::ReplaceRange { range, .. } => {
+                range.start <= range.end
+                    && string.is_char_boundary(range.start)
+                    && string.is_char_boundary(range.end)
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns the length of the string of length `len` after applying this delta to it.
+    fn applied_len(&self, len: usize) -> usize {
+        match self {
+            Self::Push(character) => len + character.len_utf8(),
+            Self::PushStr(suffix) => len + suffix.len(),
+            Self::Repeat(times) => len * times,
+            Self::ReplaceRange {
+                range,
+                replace_with,
+            } => len - range.len() + replace_with.len(),
+        }
+    }
+
+    /// Returns the number of bytes occupied by this delta including its heap memory.
+    fn internal_size(&self) -> usize {
+        size_of::<Self>()
+            + match self {
+                Self::Push(_) | Self::Repeat(_) => 0,
+                Self::PushStr(string)
+                | Self::ReplaceRange {
+                    replace_with: string,
+                    ..
+                } => string.capacity(),
+            }
+    }
+
     fn apply(&self, mut string: String) -> String {
         match self {
+            Self::Push(character) => {
+                string.push(*character);
+                string
+            }
             Self::PushStr(suffix) => {
                 string.push_str(suffix);
                 string
             }
             Self::Repeat(times) => string.repeat(*times),
+            Self::ReplaceRange {
+                range,
+                replace_with,
+            } => {
+                string.replace_range(range.clone(), replace_with);
+                string
+            }
         }
     }
 }
 
-// Manual implementation is used instead of derive to allow specifying custom allocator
-impl Default for DeltaPersistentString {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Position in the stored deltas at which a version ends.
+///
+/// The version is produced by applying the first `deltas` stored deltas
+/// followed by the first `pushed` bytes of the next one,
+/// which is a batched [`Delta::PushStr`] whenever `pushed` is positive.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
+struct Boundary {
+    deltas: usize,
+    pushed: usize,
 }
 
+/// History of versions stored as the deltas producing them from the initial version,
+/// optionally along with the full content of every `checkpoint_interval`-th version.
+///
+/// Runs of consecutive appends are batched into a single [`Delta::PushStr`]
+/// so that generating a version pushes them at once,
+/// while the boundary of each version keeps undo and redo granular per operation.
 #[cfg(feature = "allocator_api")]
-impl<A: Allocator> DeltaPersistentString<A> {
-    #[cfg(feature = "allocator_api")]
-    pub fn new_in(allocator: A) -> Self {
+#[derive(Clone, Debug)]
+pub(crate) struct DeltaHistory<A: Allocator = Global> {
+    /// Content of the initial version.
+    base: String,
+    /// Sequence of operations producing the versions following the initial one.
+    deltas: VecDeque<Delta, A>,
+    /// Positions in [`deltas`] at which the versions following the initial one end.
+    boundaries: Vec<Boundary, A>,
+    /// Contents of the versions whose indices are multiples of [`checkpoint_interval`],
+    /// the `k`-th checkpoint storing the version `(k + 1) * checkpoint_interval`.
+    checkpoints: Vec<String, A>,
+    /// Number of versions between checkpoints, if these are stored.
+    checkpoint_interval: Option<usize>,
+    /// Number of operations applied to produce the current version.
+    /// The value of `0` corresponds to the initial version.
+    current_version: usize,
+    /// Length of the current version.
+    len: usize,
+}
+#[cfg(not(feature = "allocator_api"))]
+#[derive(Clone, Debug)]
+pub(crate) struct DeltaHistory {
+    /// Content of the initial version.
+    base: String,
+    /// Sequence of operations producing the versions following the initial one.
+    deltas: VecDeque<Delta>,
+    /// Positions in [`deltas`] at which the versions following the initial one end.
+    boundaries: Vec<Boundary>,
+    /// Contents of the versions whose indices are multiples of [`checkpoint_interval`],
+    /// the `k`-th checkpoint storing the version `(k + 1) * checkpoint_interval`.
+    checkpoints: Vec<String>,
+    /// Number of versions between checkpoints, if these are stored.
+    checkpoint_interval: Option<usize>,
+    /// Number of operations applied to produce the current version.
+    /// The value of `0` corresponds to the initial version.
+    current_version: usize,
+    /// Length of the current version.
+    len: usize,
+}
+
+#[cfg(feature = "allocator_api")]
+impl<A: Allocator + Clone> DeltaHistory<A> {
+    pub(crate) fn new_in(allocator: A, checkpoint_interval: Option<usize>) -> Self {
         Self {
-            deltas: VecDeque::new_in(allocator),
+            base: String::new(),
+            deltas: VecDeque::new_in(allocator.clone()),
+            boundaries: Vec::new_in(allocator.clone()),
+            checkpoints: Vec::new_in(allocator),
+            checkpoint_interval,
             current_version: 0,
+            len: 0,
         }
     }
 }
 
-impl DeltaPersistentString {
-    pub fn new() -> Self {
+impl DeltaHistory {
+    pub(crate) fn from_base(base: String, checkpoint_interval: Option<usize>) -> Self {
         Self {
+            len: base.len(),
+            base,
             deltas: VecDeque::new(),
+            boundaries: Vec::new(),
+            checkpoints: Vec::new(),
+            checkpoint_interval,
             current_version: 0,
         }
     }
 
+    /// Creates a history with the same checkpoint interval as this one.
+    fn derive(&self, base: String, deltas: Vec<Delta>, current_version: usize) -> Self {
+        let mut history = Self::from_base(String::new(), self.checkpoint_interval);
+        history.replace_history(base, deltas, current_version);
+
+        history
+    }
+
+    /// Replaces the whole history with the versions produced by `deltas`,
+    /// one per version, from `base`.
+    fn replace_history(&mut self, base: String, deltas: Vec<Delta>, current_version: usize) {
+        self.len = base.len();
+        self.base = base;
+        self.deltas.clear();
+        self.boundaries.clear();
+        self.checkpoints.clear();
+        self.current_version = 0;
+        for delta in deltas {
+            self.push_delta(delta);
+        }
+
+        self.current_version = current_version;
+        self.len = self.generate().len();
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.deltas.reserve(additional);
+        self.boundaries.reserve(additional)
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.deltas.shrink_to_fit();
+        self.boundaries.shrink_to_fit();
+        self.checkpoints.shrink_to_fit()
+    }
+
+    pub(crate) fn checkpoint_interval(&self) -> Option<usize> {
+        self.checkpoint_interval
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn latest_version(&self) -> usize {
+        self.boundaries.len()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn checkpoint_count(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn version_capacity(&self) -> usize {
+        self.boundaries.capacity()
+    }
+
+    fn is_checkpoint(&self, version: usize) -> bool {
+        self.checkpoint_interval
+            .is_some_and(|interval| version > 0 && version.is_multiple_of(interval))
+    }
+
+    /// Returns the index of the closest version not after the given one
+    /// whose content is stored as a whole, along with this content.
+    pub(crate) fn nearest_checkpoint(&self, version: usize) -> (usize, &String) {
+        match self
+            .checkpoint_interval
+            .and_then(|interval| Some((interval, (version / interval).checked_sub(1)?)))
+        {
+            Some((interval, checkpoint)) => {
+                ((checkpoint + 1) * interval, &self.checkpoints[checkpoint])
+            }
+            None => (0, &self.base),
+        }
+    }
+
+    fn boundary(&self, version: usize) -> Boundary {
+        match version {
+            0 => Boundary::default(),
+            version => self.boundaries[version - 1],
+        }
+    }
+
+    /// Returns the boundary at which the stored deltas end.
+    fn end_boundary(&self) -> Boundary {
+        match self.deltas.back() {
+            Some(Delta::PushStr(pushed)) if !pushed.is_empty() => Boundary {
+                deltas: self.deltas.len() - 1,
+                pushed: pushed.len(),
+            },
+            _ => Boundary {
+                deltas: self.deltas.len(),
+                pushed: 0,
+            },
+        }
+    }
+
+    fn pushed_str(&self, index: usize) -> &str {
+        match &self.deltas[index] {
+            Delta::PushStr(pushed) => pushed,
+            delta => unreachable!("{delta:?} is not a batched append"),
+        }
+    }
+
+    /// Applies the stored deltas between the given boundaries to `content`.
+    fn replay(&self, from: Boundary, to: Boundary, mut content: String) -> String {
+        let mut start = from.deltas;
+        if from.pushed > 0 {
+            let pushed = self.pushed_str(from.deltas);
+            if to.deltas == from.deltas {
+                content.push_str(&pushed[from.pushed..to.pushed]);
+                return content;
+            }
+            content.push_str(&pushed[from.pushed..]);
+            start += 1;
+        }
+
+        content = self
+            .deltas
+            .range(start..to.deltas)
+            .fold(content, |accumulated, delta| delta.apply(accumulated));
+        if to.pushed > 0 {
+            content.push_str(&self.pushed_str(to.deltas)[..to.pushed]);
+        }
+
+        content
+    }
+
+    /// Returns the single operation which leads from the version ending at `from`
+    /// to the following one ending at `to`, if the boundaries point at the stored deltas.
+    fn step(&self, from: Boundary, to: Boundary) -> Option<Cow<'_, Delta>> {
+        if to.pushed > 0 {
+            let Some(Delta::PushStr(pushed)) = self.deltas.get(to.deltas) else {
+                return None;
+            };
+            let start = if from.deltas == to.deltas {
+                from.pushed
+            } else {
+                0
+            };
+
+            pushed
+                .get(start..to.pushed)
+                .map(|appended| Cow::Owned(Delta::PushStr(appended.to_string())))
+        } else {
+            to.deltas
+                .checked_sub(1)
+                .and_then(|index| self.deltas.get(index))
+                .map(Cow::Borrowed)
+        }
+    }
+
+    fn version_step(&self, version: usize) -> Cow<'_, Delta> {
+        self.step(self.boundary(version - 1), self.boundary(version))
+            .expect("boundaries should point at the stored deltas")
+    }
+
+    /// Returns the operations producing the versions following the initial one.
+    fn steps(&self) -> impl Iterator<Item = Cow<'_, Delta>> {
+        (1..=self.latest_version()).map(|version| self.version_step(version))
+    }
+
     fn generate(&self) -> String {
-        self.deltas
-            .iter()
-            .take(self.current_version)
-            .fold(String::new(), |accumulated, delta| delta.apply(accumulated))
+        self.generate_version(self.current_version)
+    }
+
+    fn generate_version(&self, version: usize) -> String {
+        let (checkpoint_version, checkpoint) = self.nearest_checkpoint(version);
+        self.replay(
+            self.boundary(checkpoint_version),
+            self.boundary(version),
+            checkpoint.clone(),
+        )
+    }
+
+    pub(crate) fn snapshot(&self) -> Cow<'_, str> {
+        let (checkpoint_version, checkpoint) = self.nearest_checkpoint(self.current_version);
+        if checkpoint_version == self.current_version {
+            Cow::Borrowed(checkpoint)
+        } else {
+            Cow::Owned(self.generate())
+        }
+    }
+
+    pub(crate) fn snapshot_at(&self, version: usize) -> Result<Cow<'_, str>, VersionSwitchError> {
+        if version <= self.latest_version() {
+            Ok(Cow::Owned(self.generate_version(version)))
+        } else {
+            Err(VersionSwitchError::NonExistent)
+        }
+    }
+
+    pub(crate) fn for_each_version(&self, mut f: impl FnMut(usize, &str)) {
+        // versions are generated incrementally instead of replaying the deltas for each one
+        let mut content = self.base.clone();
+        f(0, &content);
+        let mut previous = Boundary::default();
+        for (index, &boundary) in self.boundaries.iter().enumerate() {
+            content = self.replay(previous, boundary, content);
+            f(index + 1, &content);
+            previous = boundary;
+        }
+    }
+
+    pub(crate) fn to_delta_log(&self) -> Vec<Delta> {
+        let base = Some(&self.base)
+            .filter(|base| !base.is_empty())
+            .map(|base| Delta::PushStr(base.clone()));
+        let current = self.boundary(self.current_version);
+        let pushed = (current.pushed > 0)
+            .then(|| Delta::PushStr(self.pushed_str(current.deltas)[..current.pushed].to_string()));
+        base.into_iter()
+            .chain(self.deltas.range(..current.deltas).cloned())
+            .chain(pushed)
+            .collect()
+    }
+
+    /// Applies `delta` to the current version producing a new version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the delta's range is out of bounds or does not lie on char boundaries.
+    pub(crate) fn apply_delta(&mut self, delta: Delta) {
+        if let Delta::ReplaceRange { .. } = delta {
+            // validate the range eagerly so that an invalid delta never gets into history
+            let current = self.snapshot();
+            assert!(
+                delta.is_applicable_to(&current),
+                "{delta:?} is not applicable to a string of length {}",
+                current.len(),
+            );
+        }
+
+        self.push_delta(delta)
     }
 
-    fn push_delta(&mut self, delta: Delta) {
+    pub(crate) fn push_delta(&mut self, delta: Delta) {
         let current_version = self.current_version;
-        // there may be later deltas from which `undo` happened,
+        let current = self.boundary(current_version);
+        // there may be later versions from which `undo` happened,
         // these should no longer be reachable
-        let overwritten_deltas = self.deltas.len() - current_version;
-        for _ in 0..overwritten_deltas {
-            let popped = self.deltas.pop_back();
-            debug_assert!(popped.is_some());
+        self.boundaries.truncate(current_version);
+        if current.pushed > 0 {
+            self.deltas.truncate(current.deltas + 1);
+            if let Some(Delta::PushStr(pushed)) = self.deltas.back_mut() {
+                pushed.truncate(current.pushed);
+            }
+        } else {
+            self.deltas.truncate(current.deltas);
+        }
+        if let Some(interval) = self.checkpoint_interval {
+            self.checkpoints.truncate(current_version / interval);
+        }
+
+        let version = current_version + 1;
+        if self.is_checkpoint(version) {
+            let content = delta.apply(self.generate());
+            self.checkpoints.push(content);
         }
-        self.deltas.push_back(delta);
+        self.len = delta.applied_len(self.len);
 
-        self.current_version = current_version + 1;
+        let batched = match self.deltas.back() {
+            Some(Delta::Push(_)) => true,
+            Some(Delta::PushStr(pushed)) => !pushed.is_empty(),
+            _ => false,
+        };
+        match delta {
+            Delta::Push(_) | Delta::PushStr(_) if batched => {
+                let last = self.deltas.len() - 1;
+                if let Delta::Push(character) = self.deltas[last] {
+                    self.deltas[last] = Delta::PushStr(character.to_string());
+                    self.boundaries[current_version - 1] = self.end_boundary();
+                }
+                if let Delta::PushStr(pushed) = &mut self.deltas[last] {
+                    match delta {
+                        Delta::Push(character) => pushed.push(character),
+                        Delta::PushStr(appended) => pushed.push_str(&appended),
+                        _ => unreachable!(),
+                    }
+                }
+            }
+            delta => self.deltas.push_back(delta),
+        }
+        self.boundaries.push(self.end_boundary());
+
+        self.current_version = version;
     }
-}
 
-impl PersistentString for DeltaPersistentString {
-    // TODO: implement caching
+    pub(crate) fn rewrite_version(
+        &mut self,
+        version: usize,
+        new_content: &str,
+    ) -> Result<(), VersionSwitchError> {
+        if version > self.latest_version() {
+            return Err(VersionSwitchError::NonExistent);
+        }
 
-    fn is_empty(&self) -> bool {
-        if self.current_version > 0 {
-            self.generate().is_empty()
-        } else {
-            true
+        let mut deltas: Vec<_> = self.steps().map(Cow::into_owned).collect();
+        let mut content = new_content.to_string();
+        for delta in &deltas[version..] {
+            assert!(
+                delta.is_applicable_to(&content),
+                "{delta:?} is not applicable to the rewritten content",
+            );
+            content = delta.apply(content);
         }
+
+        let base = match version {
+            0 => new_content.to_string(),
+            version => {
+                let previous = self.generate_version(version - 1);
+                deltas[version - 1] = Delta::ReplaceRange {
+                    range: 0..previous.len(),
+                    replace_with: new_content.to_string(),
+                };
+                std::mem::take(&mut self.base)
+            }
+        };
+        self.replace_history(base, deltas, self.current_version);
+
+        Ok(())
     }
 
-    fn len(&self) -> usize {
-        if self.current_version > 0 {
-            self.generate().len()
+    pub(crate) fn version_internal_size(
+        &self,
+        version: usize,
+    ) -> Result<usize, VersionSwitchError> {
+        if version == 0 {
+            return Ok(size_of::<String>() + self.base.capacity());
+        }
+        if version > self.latest_version() {
+            return Err(VersionSwitchError::NonExistent);
+        }
+
+        let (from, to) = (self.boundary(version - 1), self.boundary(version));
+        let delta_size = if to.pushed > 0 {
+            // batched appends share a single delta, which is attributed to the first of them
+            let start = if from.deltas == to.deltas {
+                from.pushed
+            } else {
+                0
+            };
+            let header_size = if start == 0 { size_of::<Delta>() } else { 0 };
+
+            header_size + to.pushed - start
+        } else {
+            self.deltas[to.deltas - 1].internal_size()
+        };
+        let checkpoint_size = if self.is_checkpoint(version) {
+            size_of::<String>() + self.nearest_checkpoint(version).1.capacity()
         } else {
             0
+        };
+
+        Ok(size_of::<Boundary>() + delta_size + checkpoint_size)
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), VersionIntegrityError> {
+        if self.current_version > self.latest_version() {
+            return Err(VersionIntegrityError::CurrentOutOfRange);
+        }
+        let expected_checkpoints = self
+            .checkpoint_interval
+            .map_or(0, |interval| self.latest_version() / interval);
+        if self.checkpoints.len() != expected_checkpoints {
+            return Err(VersionIntegrityError::InvalidCheckpoint);
+        }
+
+        let mut content = self.base.clone();
+        let mut previous = Boundary::default();
+        for (index, &boundary) in self.boundaries.iter().enumerate() {
+            let delta = Some(previous)
+                .filter(|previous| *previous <= boundary)
+                .and_then(|previous| self.step(previous, boundary))
+                .filter(|delta| delta.is_applicable_to(&content))
+                .ok_or(VersionIntegrityError::InvalidDelta)?;
+            content = delta.apply(content);
+
+            let version = index + 1;
+            if self.is_checkpoint(version) && self.nearest_checkpoint(version).1 != &content {
+                return Err(VersionIntegrityError::InvalidCheckpoint);
+            }
+            previous = boundary;
         }
+        // every stored delta should belong to some version
+        if previous != self.end_boundary() {
+            return Err(VersionIntegrityError::InvalidDelta);
+        }
+
+        Ok(())
     }
 
-    fn snapshot(&self) -> Cow<str> {
-        Cow::Owned(self.generate())
+    pub(crate) fn collapse_no_op_versions(&mut self) {
+        let mut deltas = Vec::new();
+        let mut current_version = 0;
+        let mut content = self.base.clone();
+        for (index, delta) in self.steps().enumerate() {
+            let next_content = delta.apply(content.clone());
+            if next_content != content {
+                deltas.push(delta.into_owned());
+                content = next_content;
+            }
+            if index + 1 == self.current_version {
+                current_version = deltas.len();
+            }
+        }
+
+        let base = std::mem::take(&mut self.base);
+        self.replace_history(base, deltas, current_version);
     }
 
-    fn push_str(&mut self, string: &str) {
-        self.push_delta(Delta::PushStr(string.to_string()))
+    pub(crate) fn rebase_onto_current(&mut self) {
+        let base = self.generate();
+        let deltas = self
+            .steps()
+            .skip(self.current_version)
+            .map(Cow::into_owned)
+            .collect();
+
+        self.replace_history(base, deltas, 0);
     }
 
-    fn repeat(&mut self, times: usize) {
-        self.push_delta(Delta::Repeat(times))
+    pub(crate) fn split(&self, version: usize) -> Result<(Self, Self), VersionSwitchError> {
+        let future_base = self.snapshot_at(version)?.into_owned();
+
+        Ok((
+            self.derive(
+                self.base.clone(),
+                self.steps().take(version).map(Cow::into_owned).collect(),
+                self.current_version.min(version),
+            ),
+            self.derive(
+                future_base,
+                self.steps().skip(version).map(Cow::into_owned).collect(),
+                self.current_version.saturating_sub(version),
+            ),
+        ))
     }
 
-    fn undo(&mut self) -> Result<(), UndoError> {
+    /// Removes the bytes within `range` from the current version
+    /// returning a history whose initial version consists of them.
+    pub(crate) fn cut_range(&mut self, range: Range<usize>) -> Self {
+        let cut = self.snapshot()[range.clone()].to_string();
+        self.apply_delta(Delta::ReplaceRange {
+            range,
+            replace_with: String::new(),
+        });
+
+        self.derive(cut, Vec::new(), 0)
+    }
+
+    pub(crate) fn undo(&mut self) -> Result<(), UndoError> {
         match self.current_version {
             0 => Err(UndoError::Terminal),
             current_version => {
                 self.current_version = current_version - 1;
+                // deltas cannot be reverted thus the length has to be recomputed
+                self.len = self.generate().len();
                 Ok(())
             }
         }
     }
 
-    fn redo(&mut self) -> Result<(), RedoError> {
+    pub(crate) fn redo(&mut self) -> Result<(), RedoError> {
         let current_version = self.current_version;
-        if current_version < self.deltas.len() {
+        if current_version < self.latest_version() {
+            self.len = self.version_step(current_version + 1).applied_len(self.len);
             self.current_version = current_version + 1;
             Ok(())
         } else {
@@ -143,7 +654,387 @@ impl PersistentString for DeltaPersistentString {
     }
 }
 
+// Manual implementation is used instead of derive to allow specifying custom allocator
+impl Default for DeltaPersistentString {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl<A: Allocator + Clone> DeltaPersistentString<A> {
+    /// Creates an empty string storing its deltas in `allocator`.
+    ///
+    /// The strings held by the deltas are allocated by the global allocator
+    /// as [`String`] does not support custom allocators.
+    #[cfg(feature = "allocator_api")]
+    pub fn new_in(allocator: A) -> Self {
+        Self {
+            history: DeltaHistory::new_in(allocator, None),
+        }
+    }
+}
+
+impl DeltaPersistentString {
+    pub fn new() -> Self {
+        Self::from_base(String::new())
+    }
+
+    /// Reserves capacity for at least `additional` more versions.
+    pub fn reserve_versions(&mut self, additional: usize) {
+        self.history.reserve(additional)
+    }
+
+    /// Shrinks the capacity reserved for versions as much as possible.
+    pub fn shrink_versions_to_fit(&mut self) {
+        self.history.shrink_to_fit()
+    }
+
+    fn from_base(base: String) -> Self {
+        Self {
+            history: DeltaHistory::from_base(base, None),
+        }
+    }
+
+    /// Applies `delta` to the current version producing a new version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the delta's range is out of bounds or does not lie on char boundaries.
+    pub fn apply_delta(&mut self, delta: Delta) {
+        self.history.apply_delta(delta)
+    }
+
+    /// Replaces the content of the given version with `new_content`
+    /// replaying the deltas of all the following versions onto it.
+    ///
+    /// This is expensive as all the deltas are replayed,
+    /// and it changes the content of all the versions following the rewritten one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some following delta's range does not apply to the rewritten content,
+    /// in which case the history is left unchanged.
+    pub fn rewrite_version(
+        &mut self,
+        version: usize,
+        new_content: &str,
+    ) -> Result<(), VersionSwitchError> {
+        self.history.rewrite_version(version, new_content)
+    }
+}
+
+impl PersistentString for DeltaPersistentString {
+    fn from_chunks<'a>(chunks: impl IntoIterator<Item = &'a str>) -> Self {
+        Self::from_base(chunks.into_iter().collect())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.history.len() == 0
+    }
+
+    fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    fn snapshot(&self) -> Cow<'_, str> {
+        self.history.snapshot()
+    }
+
+    fn snapshot_at(&self, version: usize) -> Result<Cow<'_, str>, VersionSwitchError> {
+        self.history.snapshot_at(version)
+    }
+
+    fn push(&mut self, character: char) {
+        self.history.push_delta(Delta::Push(character))
+    }
+
+    fn for_each_version(&self, f: impl FnMut(usize, &str)) {
+        self.history.for_each_version(f)
+    }
+
+    fn to_delta_log(&self) -> Vec<Delta> {
+        self.history.to_delta_log()
+    }
+
+    fn push_str(&mut self, string: &str) {
+        self.history.push_delta(Delta::PushStr(string.to_string()))
+    }
+
+    fn replace_range(&mut self, range: Range<usize>, replace_with: &str) {
+        self.history.apply_delta(Delta::ReplaceRange {
+            range,
+            replace_with: replace_with.to_string(),
+        })
+    }
+
+    fn repeat(&mut self, times: usize) {
+        self.history.push_delta(Delta::Repeat(times))
+    }
+
+    fn latest_version(&self) -> usize {
+        self.history.latest_version()
+    }
+
+    fn version_internal_size(&self, version: usize) -> Result<usize, VersionSwitchError> {
+        self.history.version_internal_size(version)
+    }
+
+    fn validate_versions(&self) -> Result<(), VersionIntegrityError> {
+        self.history.validate()
+    }
+
+    fn collapse_no_op_versions(&mut self) {
+        self.history.collapse_no_op_versions()
+    }
+
+    fn rebase_onto_current(&mut self) {
+        self.history.rebase_onto_current()
+    }
+
+    fn split_history(&self, version: usize) -> Result<(Self, Self), VersionSwitchError> {
+        let (past, future) = self.history.split(version)?;
+
+        Ok((Self { history: past }, Self { history: future }))
+    }
+
+    fn cut_range(&mut self, range: Range<usize>) -> Self {
+        Self {
+            history: self.history.cut_range(range),
+        }
+    }
+
+    fn undo(&mut self) -> Result<(), UndoError> {
+        self.history.undo()
+    }
+
+    fn redo(&mut self) -> Result<(), RedoError> {
+        self.history.redo()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    crate::tests::persistent_string_test_suite!(super::DeltaPersistentString::new());
+    use super::{Boundary, Delta, DeltaPersistentString};
+    use crate::{PersistentString, VersionIntegrityError, VersionSwitchError};
+
+    crate::tests::persistent_string_test_suite!(DeltaPersistentString::new());
+
+    #[test]
+    fn test_reserve_versions() {
+        let mut string = DeltaPersistentString::new();
+        string.reserve_versions(16);
+        let capacity = string.history.boundaries.capacity();
+        assert!(capacity >= 16);
+
+        for _ in 0..16 {
+            string.push('a');
+        }
+        assert_eq!(string.history.boundaries.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_push_batching() {
+        let mut string = DeltaPersistentString::new();
+        string.push('a');
+        assert_eq!(string.history.deltas, [Delta::Push('a')]);
+        for character in "bc€🙂".chars() {
+            string.push(character);
+        }
+        assert_eq!(string.snapshot(), "abc€🙂");
+        assert_eq!(
+            string.history.deltas,
+            [Delta::PushStr("abc€🙂".to_string())]
+        );
+        assert_eq!(string.latest_version(), 5);
+
+        for expected in ["abc€", "abc", "ab", "a", ""] {
+            assert!(string.undo().is_ok());
+            assert_eq!(string.snapshot(), expected);
+            assert_eq!(string.len(), expected.len());
+        }
+        assert!(string.redo_n(3).is_ok());
+        assert_eq!(string.snapshot(), "abc");
+
+        string.push_str("de");
+        string.replace_range(0..1, "A");
+        string.push('f');
+        assert_eq!(
+            string.history.deltas,
+            [
+                Delta::PushStr("abcde".to_string()),
+                Delta::ReplaceRange {
+                    range: 0..1,
+                    replace_with: "A".to_string(),
+                },
+                Delta::Push('f'),
+            ]
+        );
+        assert_eq!(string.snapshot_at(4).unwrap(), "abcde");
+        assert_eq!(string.snapshot(), "Abcdef");
+        assert_eq!(string.validate_versions(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_versions_detects_corruption() {
+        let mut string = DeltaPersistentString::new();
+        string.push_str("foo");
+        assert_eq!(string.validate_versions(), Ok(()));
+
+        string.history.current_version = 2;
+        assert_eq!(
+            string.validate_versions(),
+            Err(VersionIntegrityError::CurrentOutOfRange)
+        );
+
+        string.history.current_version = 1;
+        string.history.deltas.push_back(Delta::ReplaceRange {
+            range: 2..8,
+            replace_with: String::new(),
+        });
+        string.history.boundaries.push(Boundary {
+            deltas: 2,
+            pushed: 0,
+        });
+        assert_eq!(
+            string.validate_versions(),
+            Err(VersionIntegrityError::InvalidDelta)
+        );
+    }
+
+    #[test]
+    fn test_to_delta_log_is_incremental() {
+        let mut string = DeltaPersistentString::from_chunks(["foo"]);
+        string.push('!');
+        string.replace_range(0..1, "b");
+        string.repeat(2);
+        assert!(string.undo().is_ok());
+
+        assert_eq!(
+            string.to_delta_log(),
+            [
+                Delta::PushStr("foo".to_string()),
+                Delta::Push('!'),
+                Delta::ReplaceRange {
+                    range: 0..1,
+                    replace_with: "b".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_version() {
+        let mut string = DeltaPersistentString::new();
+        string.push_str("password=");
+        string.push_str("hunter2");
+        string.push_str("\nuser=admin");
+        string.replace_range(0..1, "P");
+
+        assert!(string.rewrite_version(2, "password=*******").is_ok());
+        assert_eq!(string.snapshot_at(1).unwrap(), "password=");
+        assert_eq!(string.snapshot_at(2).unwrap(), "password=*******");
+        assert_eq!(string.snapshot(), "Password=*******\nuser=admin");
+        assert!(!format!("{:?}", string.history.deltas).contains("hunter2"));
+
+        assert!(string.rewrite_version(0, "").is_ok());
+        assert_eq!(string.snapshot_at(0).unwrap(), "");
+        assert_eq!(
+            string.rewrite_version(5, ""),
+            Err(VersionSwitchError::NonExistent)
+        );
+        assert_eq!(string.validate_versions(), Ok(()));
+    }
+
+    #[test]
+    fn test_cached_len() {
+        let mut string = DeltaPersistentString::from_chunks(["ab"]);
+        assert_eq!(string.len(), 2);
+
+        string.push('ç');
+        assert_eq!(string.len(), 4);
+        string.push_str("def");
+        assert_eq!(string.len(), 7);
+        string.repeat(2);
+        assert_eq!(string.len(), 14);
+        string.replace_range(0..4, "g");
+        assert_eq!(string.len(), 11);
+
+        assert!(string.undo_n(2).is_ok());
+        assert_eq!(string.len(), 7);
+        assert!(string.redo().is_ok());
+        assert_eq!(string.len(), 14);
+        assert!(string.undo_n(3).is_ok());
+        assert_eq!(string.len(), 2);
+        assert!(string.redo_n(2).is_ok());
+        assert_eq!(string.len(), 7);
+
+        string.replace_range(0..7, "");
+        assert_eq!(string.len(), 0);
+        assert!(string.is_empty());
+        assert_eq!(string.latest_version(), 3);
+        assert!(string.redo().is_err());
+
+        string.push_str("xyz");
+        assert_eq!(string.len(), string.snapshot().len());
+        string.rebase_onto_current();
+        assert_eq!(string.len(), 3);
+    }
+
+    #[test]
+    fn test_shrink_versions_to_fit() {
+        let mut string = DeltaPersistentString::new();
+        for _ in 0..64 {
+            string.push('a');
+        }
+        let capacity = string.history.boundaries.capacity();
+
+        string.rebase_onto_current();
+        string.push('b');
+        string.shrink_versions_to_fit();
+        assert!(string.history.boundaries.capacity() < capacity);
+        assert_eq!(string.snapshot(), format!("{}b", "a".repeat(64)));
+    }
+
+    #[test]
+    fn test_version_internal_size_of_append() {
+        let mut string = DeltaPersistentString::new();
+        string.push_str(&"a".repeat(1024));
+        string.push('b');
+
+        assert!(string.version_internal_size(1).unwrap() >= 1024);
+        assert!(string.version_internal_size(2).unwrap() < 1024 / 8);
+        assert_eq!(
+            string.version_internal_size(3),
+            Err(VersionSwitchError::NonExistent)
+        );
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn test_new_in_stores_versions_in_allocator() {
+        let allocator = crate::tests::CountingAllocator::default();
+        let mut string = DeltaPersistentString::new_in(&allocator);
+        string.history.deltas.reserve(4);
+        string.history.boundaries.reserve(4);
+        string.history.checkpoints.reserve(4);
+
+        assert_eq!(allocator.allocations(), 3);
+    }
+
+    #[test]
+    fn test_from_version_contents_splices_changed_regions() {
+        let string = DeltaPersistentString::from_version_contents(vec![
+            "foo bar".to_string(),
+            "föo bar".to_string(),
+        ]);
+
+        assert_eq!(
+            string.history.deltas,
+            [Delta::ReplaceRange {
+                range: 1..2,
+                replace_with: "ö".to_string(),
+            }]
+        );
+    }
 }
@@ -0,0 +1,281 @@
+/// Numbers of characters and lines inserted and deleted between two versions.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub struct DiffStats {
+    pub inserted_chars: usize,
+    pub deleted_chars: usize,
+    /// Number of line breaks among the inserted characters.
+    pub inserted_lines: usize,
+    /// Number of line breaks among the deleted characters.
+    pub deleted_lines: usize,
+}
+
+impl DiffStats {
+    /// Computes the stats of the characters of `old` and `new`
+    /// which are not in their longest common subsequence.
+    pub(crate) fn new(old: &str, new: &str) -> Self {
+        let (old, new) = (
+            old.chars().collect::<Vec<_>>(),
+            new.chars().collect::<Vec<_>>(),
+        );
+
+        let mut stats = Self::default();
+        let (mut i, mut j) = (0, 0);
+        for edit in edits(&old, &new) {
+            match edit {
+                Edit::Equal => {
+                    i += 1;
+                    j += 1;
+                }
+                Edit::Removed => {
+                    stats.deleted_chars += 1;
+                    stats.deleted_lines += usize::from(old[i] == '\n');
+                    i += 1;
+                }
+                Edit::Added => {
+                    stats.inserted_chars += 1;
+                    stats.inserted_lines += usize::from(new[j] == '\n');
+                    j += 1;
+                }
+            }
+        }
+
+        stats
+    }
+}
+
+/// An edit of the shortest edit script between two sequences.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+enum Edit {
+    /// The next items of both sequences are equal.
+    Equal,
+    /// The next item of the old sequence is removed.
+    Removed,
+    /// The next item of the new sequence is added.
+    Added,
+}
+
+/// Computes the edits turning `old` into `new` based on their longest common subsequence.
+fn edits<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    // `common[i][j]` is the length of the longest common subsequence of `old[i..]` and `new[j..]`
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push(Edit::Equal);
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            edits.push(Edit::Removed);
+            i += 1;
+        } else {
+            edits.push(Edit::Added);
+            j += 1;
+        }
+    }
+
+    edits
+}
+
+/// An operation of a line-granular diff between two versions.
+///
+/// Lines include their terminating `\n`, if any, so that line breaks are compared as well.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum LineDiffOp {
+    /// The line is present in both versions.
+    Equal(String),
+    /// The line is only present in the new version.
+    Added(String),
+    /// The line is only present in the old version.
+    Removed(String),
+}
+
+/// Computes the diff between the lines of `old` and `new`
+/// based on their longest common subsequence.
+pub(crate) fn line_diff(old: &str, new: &str) -> Vec<LineDiffOp> {
+    let (old, new) = (
+        old.split_inclusive('\n').collect::<Vec<_>>(),
+        new.split_inclusive('\n').collect::<Vec<_>>(),
+    );
+
+    let (mut i, mut j) = (0, 0);
+    edits(&old, &new)
+        .into_iter()
+        .map(|edit| match edit {
+            Edit::Equal => {
+                i += 1;
+                j += 1;
+                LineDiffOp::Equal(old[i - 1].to_string())
+            }
+            Edit::Removed => {
+                i += 1;
+                LineDiffOp::Removed(old[i - 1].to_string())
+            }
+            Edit::Added => {
+                j += 1;
+                LineDiffOp::Added(new[j - 1].to_string())
+            }
+        })
+        .collect()
+}
+
+/// Formats the line diff operations as unified diff hunks
+/// keeping `context` unchanged lines around the changes.
+pub(crate) fn unified_diff(operations: &[LineDiffOp], context: usize) -> String {
+    // positions of each operation's line in the old and the new version
+    let mut positions = Vec::with_capacity(operations.len() + 1);
+    let (mut old_position, mut new_position) = (0, 0);
+    for operation in operations {
+        positions.push((old_position, new_position));
+        match operation {
+            LineDiffOp::Equal(_) => {
+                old_position += 1;
+                new_position += 1;
+            }
+            LineDiffOp::Removed(_) => old_position += 1,
+            LineDiffOp::Added(_) => new_position += 1,
+        }
+    }
+    positions.push((old_position, new_position));
+
+    let mut changes = operations
+        .iter()
+        .enumerate()
+        .filter(|(_, operation)| !matches!(operation, LineDiffOp::Equal(_)))
+        .map(|(index, _)| index)
+        .peekable();
+
+    let mut diff = String::new();
+    while let Some(first_change) = changes.next() {
+        let start = first_change.saturating_sub(context);
+        let mut end = (first_change + 1 + context).min(operations.len());
+        while let Some(change) = changes.next_if(|&change| change - context.min(change) <= end) {
+            end = (change + 1 + context).min(operations.len());
+        }
+
+        let ((old_start, new_start), (old_end, new_end)) = (positions[start], positions[end]);
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_end - old_start),
+            hunk_range(new_start, new_end - new_start),
+        ));
+        for operation in &operations[start..end] {
+            let (prefix, line) = match operation {
+                LineDiffOp::Equal(line) => (' ', line),
+                LineDiffOp::Removed(line) => ('-', line),
+                LineDiffOp::Added(line) => ('+', line),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    diff
+}
+
+/// Formats the range of a hunk the way GNU diff does.
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        // an empty range is denoted by the line preceding it
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        count => format!("{},{count}", start + 1),
+    }
+}
+
+/// Merges the changes made to the lines of `base` in `ours` and in `theirs`,
+/// returning the merged content and whether there were conflicting changes.
+///
+/// Lines are compared along with their terminators, so cleanly merged content
+/// keeps its line breaks intact. Conflicting regions are surrounded by diff3-style
+/// conflict markers, each on its own line.
+pub(crate) fn merge_with_markers(base: &str, ours: &str, theirs: &str) -> (String, bool) {
+    let (base_lines, our_lines, their_lines) = (
+        base.split_inclusive('\n').collect::<Vec<_>>(),
+        ours.split_inclusive('\n').collect::<Vec<_>>(),
+        theirs.split_inclusive('\n').collect::<Vec<_>>(),
+    );
+    let (our_matches, their_matches) = (
+        base_line_matches(base_lines.len(), &line_diff(base, ours)),
+        base_line_matches(base_lines.len(), &line_diff(base, theirs)),
+    );
+
+    let mut merged = String::with_capacity(base.len().max(ours.len()).max(theirs.len()));
+    let mut conflicted = false;
+    let (mut base_start, mut our_start, mut their_start) = (0, 0, 0);
+    loop {
+        // the next base line kept unchanged by both sides synchronizes the three versions
+        let sync = (base_start..base_lines.len())
+            .find_map(|line| Some((line, our_matches[line]?, their_matches[line]?)));
+        let (base_end, our_end, their_end) =
+            sync.unwrap_or((base_lines.len(), our_lines.len(), their_lines.len()));
+
+        let (base_chunk, our_chunk, their_chunk) = (
+            &base_lines[base_start..base_end],
+            &our_lines[our_start..our_end],
+            &their_lines[their_start..their_end],
+        );
+        if our_chunk == base_chunk {
+            merged.extend(their_chunk.iter().copied());
+        } else if their_chunk == base_chunk || our_chunk == their_chunk {
+            merged.extend(our_chunk.iter().copied());
+        } else {
+            conflicted = true;
+            for (marker, chunk) in [
+                ("<<<<<<< ours\n", our_chunk),
+                ("||||||| base\n", base_chunk),
+                ("=======\n", their_chunk),
+            ] {
+                merged.push_str(marker);
+                merged.extend(chunk.iter().copied());
+                // the last line of the content may lack the line break separating it from the marker
+                if chunk.last().is_some_and(|line| !line.ends_with('\n')) {
+                    merged.push('\n');
+                }
+            }
+            merged.push_str(">>>>>>> theirs\n");
+        }
+
+        match sync {
+            Some((base_line, _, _)) => {
+                merged.push_str(base_lines[base_line]);
+                (base_start, our_start, their_start) = (base_end + 1, our_end + 1, their_end + 1);
+            }
+            None => break,
+        }
+    }
+
+    (merged, conflicted)
+}
+
+/// Returns the index of the line of the new version matching each line of the old one, if any.
+fn base_line_matches(base_len: usize, operations: &[LineDiffOp]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base_len];
+    let (mut old_position, mut new_position) = (0, 0);
+    for operation in operations {
+        match operation {
+            LineDiffOp::Equal(_) => {
+                matches[old_position] = Some(new_position);
+                old_position += 1;
+                new_position += 1;
+            }
+            LineDiffOp::Removed(_) => old_position += 1,
+            LineDiffOp::Added(_) => new_position += 1,
+        }
+    }
+
+    matches
+}
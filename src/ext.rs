@@ -0,0 +1,40 @@
+use std::ops::Range;
+
+use crate::PersistentString;
+
+/// Extension of [`PersistentString`] providing chainable versions of its mutating operations.
+///
+/// These are kept out of [`PersistentString`] so that it stays object-safe.
+pub trait PersistentStringExt: PersistentString {
+    fn then_push(&mut self, character: char) -> &mut Self {
+        self.push(character);
+        self
+    }
+
+    fn then_push_str(&mut self, string: &str) -> &mut Self {
+        self.push_str(string);
+        self
+    }
+
+    fn then_insert_str(&mut self, index: usize, string: &str) -> &mut Self {
+        self.insert_str(index, string);
+        self
+    }
+
+    fn then_replace_range(&mut self, range: Range<usize>, replace_with: &str) -> &mut Self {
+        self.replace_range(range, replace_with);
+        self
+    }
+
+    fn then_repeat(&mut self, times: usize) -> &mut Self {
+        self.repeat(times);
+        self
+    }
+
+    fn then_clear(&mut self) -> &mut Self {
+        self.clear();
+        self
+    }
+}
+
+impl<S: PersistentString + ?Sized> PersistentStringExt for S {}
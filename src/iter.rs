@@ -0,0 +1,28 @@
+use std::borrow::Cow;
+
+/// Iterator over the characters of a [`PersistentString`](crate::PersistentString) version
+/// in reverse order.
+#[derive(Clone, Debug)]
+pub struct CharsRev<'a> {
+    snapshot: Cow<'a, str>,
+    /// Byte index up to which characters have not been yielded yet.
+    end: usize,
+}
+
+impl<'a> CharsRev<'a> {
+    pub(crate) fn new(snapshot: Cow<'a, str>) -> Self {
+        let end = snapshot.len();
+        Self { snapshot, end }
+    }
+}
+
+impl Iterator for CharsRev<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.snapshot[..self.end].chars().next_back()?;
+        self.end -= next.len_utf8();
+
+        Some(next)
+    }
+}
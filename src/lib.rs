@@ -1,34 +1,1041 @@
 #![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 
+pub use checkpointed::CheckpointedDeltaString;
 pub use cow::CowPersistentString;
-pub use delta::DeltaPersistentString;
-use std::borrow::Cow;
+pub use delta::{Delta, DeltaPersistentString};
+pub use diff::{DiffStats, LineDiffOp};
+pub use ext::PersistentStringExt;
+pub use iter::CharsRev;
+pub use operation::{Operation, OperationRecorder};
+pub use ot::{OtError, OtOp};
+use std::{
+    borrow::Cow, cmp::Ordering, collections::hash_map::DefaultHasher, hash::Hasher, ops::Range,
+    str::Utf8Error,
+};
+pub use transaction::EditTransaction;
+pub use view::ReadOnlyView;
 
+mod checkpointed;
 mod cow;
 mod delta;
+mod diff;
+mod ext;
+mod iter;
+mod operation;
+mod ot;
 #[cfg(test)]
 pub(crate) mod tests;
+mod transaction;
+mod view;
 
 /// A string providing persistent operations.
+///
+/// The trait is object-safe, methods which would break this are bound by `Self: Sized`.
 pub trait PersistentString {
+    // Construction operations
+
+    /// Creates a string whose initial version is the concatenation of `chunks`.
+    fn from_chunks<'a>(chunks: impl IntoIterator<Item = &'a str>) -> Self
+    where
+        Self: Sized;
+
+    /// Creates a string whose `i`-th version is `versions[i]` with the last one being current.
+    ///
+    /// Each version is produced by replacing only the region changed since the previous one.
+    /// If `versions` is empty, the string only has an empty initial version.
+    fn from_version_contents(versions: Vec<String>) -> Self
+    where
+        Self: Sized,
+    {
+        let mut versions = versions.into_iter();
+        let mut string = Self::with_content(&versions.next().unwrap_or_default());
+        for version in versions {
+            let current = string.snapshot();
+            let prefix_len = current
+                .chars()
+                .zip(version.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(common, _)| common.len_utf8())
+                .sum::<usize>();
+            let suffix_len = current[prefix_len..]
+                .chars()
+                .rev()
+                .zip(version[prefix_len..].chars().rev())
+                .take_while(|(a, b)| a == b)
+                .map(|(common, _)| common.len_utf8())
+                .sum::<usize>();
+            let current_len = current.len();
+            drop(current);
+
+            string.replace_range(
+                prefix_len..current_len - suffix_len,
+                &version[prefix_len..version.len() - suffix_len],
+            );
+        }
+
+        string
+    }
+
+    /// Creates a string whose initial version is the concatenation
+    /// of the current versions of all `parts`.
+    fn concat_all<I: IntoIterator<Item = Self>>(parts: I) -> Self
+    where
+        Self: Sized,
+    {
+        let parts = parts.into_iter().collect::<Vec<_>>();
+        let snapshots = parts.iter().map(Self::snapshot).collect::<Vec<_>>();
+
+        Self::from_chunks(snapshots.iter().map(AsRef::as_ref))
+    }
+
+    /// Creates a string whose initial version is `content`.
+    fn with_content(content: &str) -> Self
+    where
+        Self: Sized,
+    {
+        Self::from_chunks([content])
+    }
+
     // State-checking operations
 
     fn is_empty(&self) -> bool;
 
+    /// Returns the length of the current version in bytes, same as [`byte_len`](Self::byte_len).
+    ///
+    /// Prefer [`byte_len`](Self::byte_len) or [`char_len`](Self::char_len)
+    /// where the unit is not obvious from the context.
     fn len(&self) -> usize;
 
+    /// Returns the length of the current version in bytes.
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    /// Returns the length of the current version in characters.
+    fn char_len(&self) -> usize {
+        self.snapshot().chars().count()
+    }
+
+    /// Returns the character at the given 0-based line and (char) column of the current version,
+    /// or [`None`] if there is no such position.
+    fn char_at_line_col(&self, line: usize, col: usize) -> Option<char> {
+        self.nth_line(line)?.chars().nth(col)
+    }
+
+    /// Converts a byte index of the current version into a char index,
+    /// returning [`None`] if it is out of range or not on a char boundary.
+    fn byte_to_char(&self, byte_index: usize) -> Option<usize> {
+        let snapshot = self.snapshot();
+        if snapshot.is_char_boundary(byte_index) {
+            Some(snapshot[..byte_index].chars().count())
+        } else {
+            None
+        }
+    }
+
+    /// Converts a char index of the current version into a byte index,
+    /// returning [`None`] if it is out of range.
+    fn char_to_byte(&self, char_index: usize) -> Option<usize> {
+        let snapshot = self.snapshot();
+        snapshot
+            .char_indices()
+            .map(|(byte_index, _)| byte_index)
+            .chain(std::iter::once(snapshot.len()))
+            .nth(char_index)
+    }
+
+    /// Checks if the current version contains `needle` starting exactly at byte `index`.
+    fn matches_at(&self, index: usize, needle: &str) -> bool {
+        self.snapshot()
+            .as_bytes()
+            .get(index..)
+            .is_some_and(|tail| tail.starts_with(needle.as_bytes()))
+    }
+
     // Copying operations
 
-    fn snapshot(&self) -> Cow<str>;
+    fn snapshot(&self) -> Cow<'_, str>;
+
+    /// Returns the content of the current version as an owned string.
+    ///
+    /// Backends storing the content as a whole still have to copy it.
+    fn snapshot_owned(&self) -> String {
+        self.snapshot().into_owned()
+    }
+
+    /// Returns the exact length in bytes of the [snapshot](Self::snapshot) of the current version.
+    fn snapshot_len(&self) -> usize {
+        self.len()
+    }
+
+    /// Appends the content of the current version to `buffer`
+    /// reserving the space for it up front.
+    fn snapshot_into_string(&self, buffer: &mut String) {
+        buffer.reserve(self.snapshot_len());
+        buffer.push_str(&self.snapshot())
+    }
+
+    /// Returns a sequence of deltas which reconstructs the content of the current version
+    /// when applied to an empty [`DeltaPersistentString`].
+    ///
+    /// Backends tracking operations return them, others return the content as a single delta.
+    fn to_delta_log(&self) -> Vec<Delta> {
+        let snapshot = self.snapshot();
+        if snapshot.is_empty() {
+            Vec::new()
+        } else {
+            vec![Delta::PushStr(snapshot.into_owned())]
+        }
+    }
+
+    /// Returns the number of lines of the current version: `0` if it is empty
+    /// and otherwise the number of `\n` plus one, so a trailing `\n` starts an empty last line.
+    fn line_count(&self) -> usize {
+        let snapshot = self.snapshot();
+        if snapshot.is_empty() {
+            0
+        } else {
+            snapshot.matches('\n').count() + 1
+        }
+    }
+
+    /// Returns the byte offset at which the given 0-based line of the current version starts,
+    /// or [`None`] if there are not as many [lines](Self::line_count).
+    fn line_start_offset(&self, line: usize) -> Option<usize> {
+        match line {
+            0 => (!self.is_empty()).then_some(0),
+            line => self
+                .snapshot()
+                .match_indices('\n')
+                .nth(line - 1)
+                .map(|(index, _)| index + 1),
+        }
+    }
+
+    /// Returns the `n`-th (0-based) line of the current version, lines being separated by `\n`,
+    /// or [`None`] if there are not as many [lines](Self::line_count).
+    fn nth_line(&self, n: usize) -> Option<Cow<'_, str>> {
+        match self.snapshot() {
+            snapshot if snapshot.is_empty() => None,
+            Cow::Borrowed(snapshot) => snapshot.split('\n').nth(n).map(Cow::Borrowed),
+            Cow::Owned(snapshot) => snapshot
+                .split('\n')
+                .nth(n)
+                .map(|line| Cow::Owned(line.to_string())),
+        }
+    }
+
+    /// Returns the content of the given version without switching to it.
+    ///
+    /// Versions are numbered from `0`, the initial version (empty unless rebased),
+    /// up to [`latest_version`](Self::latest_version) including the ones reachable via `redo`.
+    fn snapshot_at(&self, version: usize) -> Result<Cow<'_, str>, VersionSwitchError>;
+
+    /// Returns the bytes of the given version within `range` without switching to it,
+    /// or [`None`] if there is no such version or the range is out of its bounds.
+    fn byte_slice_at(&self, version: usize, range: Range<usize>) -> Option<Cow<'_, [u8]>> {
+        match self.snapshot_at(version).ok()? {
+            Cow::Borrowed(snapshot) => snapshot.as_bytes().get(range).map(Cow::Borrowed),
+            Cow::Owned(snapshot) => snapshot
+                .as_bytes()
+                .get(range)
+                .map(|bytes| Cow::Owned(bytes.to_vec())),
+        }
+    }
+
+    /// Checks if the given version is empty without switching to it.
+    fn is_empty_at(&self, version: usize) -> Result<bool, VersionSwitchError> {
+        self.snapshot_at(version)
+            .map(|snapshot| snapshot.is_empty())
+    }
+
+    /// Checks if the given version starts with `prefix` without switching to it.
+    fn starts_with_at(&self, version: usize, prefix: &str) -> Result<bool, VersionSwitchError> {
+        self.snapshot_at(version)
+            .map(|snapshot| snapshot.starts_with(prefix))
+    }
+
+    /// Returns the lowest version whose content matches `predicate`.
+    fn find_version(&self, predicate: impl Fn(&str) -> bool) -> Option<usize>
+    where
+        Self: Sized,
+    {
+        (0..=self.latest_version()).find(|&version| {
+            self.snapshot_at(version)
+                .is_ok_and(|snapshot| predicate(&snapshot))
+        })
+    }
+
+    /// Compares the contents of versions `a` and `b` lexicographically.
+    fn compare_versions(&self, a: usize, b: usize) -> Result<Ordering, VersionSwitchError> {
+        Ok(self.snapshot_at(a)?.cmp(&self.snapshot_at(b)?))
+    }
+
+    /// Returns the hash of the given version's content.
+    ///
+    /// The hash is stable for the same content within the same build of the crate.
+    fn version_hash(&self, version: usize) -> Result<u64, VersionSwitchError> {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(self.snapshot_at(version)?.as_bytes());
+
+        Ok(hasher.finish())
+    }
+
+    /// Calls `f` with the number and the content of each version in order.
+    ///
+    /// At most one version's content is held at a time.
+    fn for_each_version(&self, mut f: impl FnMut(usize, &str))
+    where
+        Self: Sized,
+    {
+        for version in 0..=self.latest_version() {
+            f(
+                version,
+                &self
+                    .snapshot_at(version)
+                    .expect("versions up to the latest one should exist"),
+            );
+        }
+    }
+
+    /// Returns the [hash](Self::version_hash) of each version's content indexed by version.
+    fn version_hashes(&self) -> Vec<u64> {
+        (0..=self.latest_version())
+            .map(|version| {
+                self.version_hash(version)
+                    .expect("versions up to the latest one should exist")
+            })
+            .collect()
+    }
+
+    /// Returns the byte length of the common prefix of the two versions' contents.
+    ///
+    /// The returned length always lies on a char boundary of both versions.
+    fn common_prefix_len(&self, a: usize, b: usize) -> Result<usize, VersionSwitchError> {
+        let (a, b) = (self.snapshot_at(a)?, self.snapshot_at(b)?);
+
+        Ok(a.chars()
+            .zip(b.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(common, _)| common.len_utf8())
+            .sum())
+    }
+
+    /// Returns the byte length of the common suffix of the two versions' contents.
+    ///
+    /// The returned length always lies on a char boundary of both versions.
+    /// Note that it is computed independently of [`common_prefix_len`](Self::common_prefix_len)
+    /// so the two may overlap when one version is a repetition of the other.
+    fn common_suffix_len(&self, a: usize, b: usize) -> Result<usize, VersionSwitchError> {
+        let (a, b) = (self.snapshot_at(a)?, self.snapshot_at(b)?);
+
+        Ok(a.chars()
+            .rev()
+            .zip(b.chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(common, _)| common.len_utf8())
+            .sum())
+    }
+
+    /// Returns the length of the unchanged prefix of the two versions' contents
+    /// followed by the byte ranges of `a` and `b` which differ.
+    ///
+    /// This is a cheap alternative to a full diff as it only bounds the changed region
+    /// by the common prefix and the (non-overlapping) common suffix.
+    fn changed_region(
+        &self,
+        a: usize,
+        b: usize,
+    ) -> Result<(usize, Range<usize>, Range<usize>), VersionSwitchError> {
+        let prefix = self.common_prefix_len(a, b)?;
+        let (a_len, b_len) = (self.snapshot_at(a)?.len(), self.snapshot_at(b)?.len());
+        let suffix = self.common_suffix_len(a, b)?.min(a_len.min(b_len) - prefix);
+
+        Ok((prefix, prefix..a_len - suffix, prefix..b_len - suffix))
+    }
+
+    /// Returns the numbers of characters and lines inserted and deleted
+    /// when going from version `a` to version `b`.
+    ///
+    /// The characters not in the longest common subsequence of the versions' contents
+    /// are accounted, the comparison being limited to their [changed region](Self::changed_region).
+    fn diff_stats(&self, a: usize, b: usize) -> Result<DiffStats, VersionSwitchError> {
+        let (_, a_range, b_range) = self.changed_region(a, b)?;
+
+        Ok(DiffStats::new(
+            &self.snapshot_at(a)?[a_range],
+            &self.snapshot_at(b)?[b_range],
+        ))
+    }
+
+    /// Returns the line-granular diff from version `a` to version `b`
+    /// based on the longest common subsequence of their lines.
+    fn line_diff(&self, a: usize, b: usize) -> Result<Vec<LineDiffOp>, VersionSwitchError> {
+        Ok(diff::line_diff(
+            &self.snapshot_at(a)?,
+            &self.snapshot_at(b)?,
+        ))
+    }
+
+    /// Returns the [line diff](Self::line_diff) from version `a` to version `b`
+    /// formatted as unified diff hunks with `context` unchanged lines around changes.
+    ///
+    /// The `---` and `+++` file headers are not included.
+    /// A last line lacking the trailing `\n` is followed by `\ No newline at end of file`.
+    fn unified_diff(
+        &self,
+        a: usize,
+        b: usize,
+        context: usize,
+    ) -> Result<String, VersionSwitchError> {
+        Ok(diff::unified_diff(&self.line_diff(a, b)?, context))
+    }
+
+    /// Returns an iterator over the characters of the current version in reverse order.
+    fn chars_rev(&self) -> CharsRev<'_> {
+        CharsRev::new(self.snapshot())
+    }
+
+    /// Returns a view of this string which only allows non-mutating operations.
+    fn view(&self) -> ReadOnlyView<'_>
+    where
+        Self: Sized,
+    {
+        ReadOnlyView::new(self)
+    }
 
     // Mutating operations
 
+    fn push(&mut self, character: char) {
+        self.push_str(character.encode_utf8(&mut [0; 4]))
+    }
+
     fn push_str(&mut self, string: &str);
 
+    /// Appends a character or a string
+    /// dispatching to [`push`](Self::push) or [`push_str`](Self::push_str) respectively.
+    fn append<'a>(&mut self, value: impl Into<Appendable<'a>>)
+    where
+        Self: Sized,
+    {
+        match value.into() {
+            Appendable::Char(character) => self.push(character),
+            Appendable::Str(string) => self.push_str(&string),
+        }
+    }
+
+    /// Appends each line of `text` (including its `\n`) as a separate version.
+    fn push_lines(&mut self, text: &str) {
+        for line in text.split_inclusive('\n') {
+            self.push_str(line);
+        }
+    }
+
+    /// Appends the bytes converted to a string, replacing invalid UTF-8 sequences
+    /// with [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER).
+    fn push_bytes_lossy(&mut self, bytes: &[u8]) {
+        self.push_str(&String::from_utf8_lossy(bytes))
+    }
+
+    /// Appends `fill` until the current version is `target_chars` characters long.
+    ///
+    /// No version is produced if it is not shorter.
+    fn pad_end(&mut self, target_chars: usize, fill: char) {
+        let char_len = self.char_len();
+        if char_len < target_chars {
+            let padding = fill.to_string().repeat(target_chars - char_len);
+            self.push_str(&padding)
+        }
+    }
+
+    /// Prepends `fill` until the current version is `target_chars` characters long.
+    ///
+    /// No version is produced if it is not shorter.
+    fn pad_start(&mut self, target_chars: usize, fill: char) {
+        let char_len = self.char_len();
+        if char_len < target_chars {
+            let padding = fill.to_string().repeat(target_chars - char_len);
+            self.insert_str(0, &padding)
+        }
+    }
+
+    /// Removes the last character of the current version returning it.
+    ///
+    /// No version is produced if the string is empty.
+    fn pop(&mut self) -> Option<char> {
+        let last = self.chars_rev().next()?;
+        let len = self.len();
+        self.replace_range(len - last.len_utf8()..len, "");
+
+        Some(last)
+    }
+
+    /// Removes the trailing characters of the current version while they match `predicate`
+    /// returning them in their original order.
+    ///
+    /// No version is produced if no characters match.
+    fn pop_while(&mut self, predicate: impl Fn(char) -> bool) -> String
+    where
+        Self: Sized,
+    {
+        let len = self.len();
+        let popped_len = self
+            .chars_rev()
+            .take_while(|&character| predicate(character))
+            .map(char::len_utf8)
+            .sum::<usize>();
+        if popped_len == 0 {
+            return String::new();
+        }
+
+        let popped = self.snapshot()[len - popped_len..].to_string();
+        self.replace_range(len - popped_len..len, "");
+
+        popped
+    }
+
+    /// Inserts `string` at byte `index` of the current version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds or does not lie on a char boundary.
+    fn insert_str(&mut self, index: usize, string: &str) {
+        self.replace_range(index..index, string)
+    }
+
+    /// Inserts `string` at byte `index` of the current version
+    /// returning an error without producing a version if the index is invalid.
+    fn try_insert_str(&mut self, index: usize, string: &str) -> Result<(), IndexError> {
+        let snapshot = self.snapshot();
+        if index > snapshot.len() {
+            return Err(IndexError::OutOfBounds);
+        }
+        if !snapshot.is_char_boundary(index) {
+            return Err(IndexError::NotCharBoundary);
+        }
+        drop(snapshot);
+
+        self.insert_str(index, string);
+        Ok(())
+    }
+
+    /// Inserts `string` at the closest char boundary not after byte `approx_index`
+    /// of the current version returning the byte index at which it was inserted.
+    ///
+    /// Indices past the end of the current version insert at its end.
+    fn insert_str_nearest(&mut self, approx_index: usize, string: &str) -> usize {
+        let snapshot = self.snapshot();
+        let index = (0..=approx_index.min(snapshot.len()))
+            .rev()
+            .find(|&index| snapshot.is_char_boundary(index))
+            .expect("zero should be a char boundary");
+        drop(snapshot);
+
+        self.insert_str(index, string);
+        index
+    }
+
+    /// Inserts the bytes within `other_range` of `other`'s current version
+    /// at byte `index` of the current version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` or `other_range` are out of bounds or do not lie on char boundaries.
+    fn insert_subrange(
+        &mut self,
+        index: usize,
+        other: &dyn PersistentString,
+        other_range: Range<usize>,
+    ) {
+        self.insert_str(index, &other.snapshot()[other_range])
+    }
+
+    /// Removes the character at byte `index` of the current version returning it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds or does not lie on a char boundary.
+    fn remove(&mut self, index: usize) -> char {
+        let removed = self.snapshot()[index..]
+            .chars()
+            .next()
+            .expect("index should be less than the length of the string");
+        self.replace_range(index..index + removed.len_utf8(), "");
+
+        removed
+    }
+
+    /// Removes the given byte range of the current version
+    /// returning a new string whose initial version holds the removed content.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or does not lie on char boundaries.
+    fn cut_range(&mut self, range: Range<usize>) -> Self
+    where
+        Self: Sized;
+
+    /// Clears the current version returning a new string whose initial version
+    /// holds the former content.
+    fn take(&mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.cut_range(0..self.len())
+    }
+
+    /// Keeps only the characters of the current version matching `filter`
+    /// which is called exactly once for each character in order.
+    fn retain(&mut self, mut filter: impl FnMut(char) -> bool)
+    where
+        Self: Sized,
+    {
+        let retained = self
+            .snapshot()
+            .chars()
+            .filter(|&character| filter(character))
+            .collect::<String>();
+        self.replace_range(0..self.len(), &retained)
+    }
+
+    /// Keeps only the characters of the current version matching `filter`
+    /// returning the number of removed ones.
+    fn retain_counting(&mut self, mut filter: impl FnMut(char) -> bool) -> usize
+    where
+        Self: Sized,
+    {
+        let mut removed = 0;
+        self.retain(|character| {
+            let keep = filter(character);
+            if !keep {
+                removed += 1;
+            }
+
+            keep
+        });
+
+        removed
+    }
+
+    /// Removes the characters of the current version matching `filter`
+    /// returning them in order.
+    fn extract(&mut self, mut filter: impl FnMut(char) -> bool) -> String
+    where
+        Self: Sized,
+    {
+        let mut extracted = String::new();
+        self.retain(|character| {
+            let matches = filter(character);
+            if matches {
+                extracted.push(character);
+            }
+
+            !matches
+        });
+
+        extracted
+    }
+
+    /// Keeps only the characters of the current version matching `filter`
+    /// producing a new version only if some were removed, returning whether one was produced.
+    fn retain_if_changed(&mut self, mut filter: impl FnMut(char) -> bool) -> bool
+    where
+        Self: Sized,
+    {
+        let retained = self
+            .snapshot()
+            .chars()
+            .filter(|&character| filter(character))
+            .collect::<String>();
+        let len = self.len();
+        if retained.len() == len {
+            false
+        } else {
+            self.replace_range(0..len, &retained);
+            true
+        }
+    }
+
+    /// Keeps only the bytes of the current version matching `filter`
+    /// which is called exactly once for each byte in order.
+    ///
+    /// Filtering out only some bytes of a multibyte character breaks UTF-8,
+    /// in which case an error is returned and no version is produced.
+    fn retain_bytes(&mut self, mut filter: impl FnMut(u8) -> bool) -> Result<(), Utf8Error>
+    where
+        Self: Sized,
+    {
+        let retained = self
+            .snapshot()
+            .bytes()
+            .filter(|&byte| filter(byte))
+            .collect::<Vec<_>>();
+        let retained = String::from_utf8(retained).map_err(|error| error.utf8_error())?;
+        self.replace_range(0..self.len(), &retained);
+
+        Ok(())
+    }
+
+    /// Keeps only the characters within the byte `range` of the current version
+    /// which match `filter`, characters outside of it are kept unconditionally.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or does not lie on char boundaries.
+    fn retain_range(&mut self, range: Range<usize>, mut filter: impl FnMut(char) -> bool)
+    where
+        Self: Sized,
+    {
+        let retained = self.snapshot()[range.clone()]
+            .chars()
+            .filter(|&character| filter(character))
+            .collect::<String>();
+        self.replace_range(range, &retained)
+    }
+
+    /// Replaces the content of the current version with `new_content`
+    /// producing a new version only if it differs, returning whether one was produced.
+    fn set_content(&mut self, new_content: &str) -> bool {
+        if self.snapshot() == new_content {
+            false
+        } else {
+            self.replace_range(0..self.len(), new_content);
+            true
+        }
+    }
+
+    fn clear(&mut self) {
+        self.replace_range(0..self.len(), "")
+    }
+
     fn repeat(&mut self, times: usize);
 
+    /// Prepends `prefix` to every non-empty line of the current version.
+    fn indent_lines(&mut self, prefix: &str) {
+        let indented = self
+            .snapshot()
+            .split_inclusive('\n')
+            .flat_map(|line| [if line == "\n" { "" } else { prefix }, line])
+            .collect::<String>();
+        self.replace_range(0..self.len(), &indented)
+    }
+
+    /// Removes `prefix` from the start of every line of the current version starting with it.
+    fn dedent_lines(&mut self, prefix: &str) {
+        let dedented = self
+            .snapshot()
+            .split_inclusive('\n')
+            .map(|line| line.strip_prefix(prefix).unwrap_or(line))
+            .collect::<String>();
+        self.replace_range(0..self.len(), &dedented)
+    }
+
+    /// Replaces the given 0-based range of lines of the current version with `replacement`.
+    ///
+    /// A `\n` is appended to a non-empty `replacement` not ending with one
+    /// if it is followed by more lines, so that the lines following the range stay separate.
+    /// Removing the trailing lines with an empty `replacement` also removes the `\n` preceding them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of `line_range` is greater than its end
+    /// or its end is greater than [`line_count`](Self::line_count).
+    fn replace_lines(&mut self, line_range: Range<usize>, replacement: &str) {
+        let line_count = self.line_count();
+        assert!(
+            line_range.start <= line_range.end && line_range.end <= line_count,
+            "line range should be within the line count"
+        );
+
+        let len = self.len();
+        let (mut start, separator) = match self.line_start_offset(line_range.start) {
+            Some(start) => (start, ""),
+            // there is no line to separate the replacement from
+            None if len == 0 => (0, ""),
+            None => (len, "\n"),
+        };
+        let end = if line_range.end == line_count {
+            len
+        } else {
+            self.line_start_offset(line_range.end)
+                .expect("line should exist as it is less than the line count")
+        };
+        if replacement.is_empty() && !line_range.is_empty() && end == len && start > 0 {
+            // otherwise the removed lines would leave an empty line behind
+            start -= 1;
+        }
+        let replacement = if replacement.is_empty() {
+            Cow::Borrowed("")
+        } else if end != len && !replacement.ends_with('\n') {
+            Cow::Owned(format!("{separator}{replacement}\n"))
+        } else {
+            Cow::Owned(format!("{separator}{replacement}"))
+        };
+
+        self.replace_range(start..end, &replacement)
+    }
+
+    /// Converts all line endings (`\n`, `\r\n` and lone `\r`) of the current version
+    /// to the given style.
+    fn normalize_newlines(&mut self, style: NewlineStyle) {
+        let snapshot = self.snapshot();
+        let mut normalized = String::with_capacity(snapshot.len());
+        let mut characters = snapshot.chars().peekable();
+        while let Some(character) = characters.next() {
+            match character {
+                '\r' => {
+                    characters.next_if_eq(&'\n');
+                    normalized.push_str(style.as_str());
+                }
+                '\n' => normalized.push_str(style.as_str()),
+                character => normalized.push(character),
+            }
+        }
+        drop(snapshot);
+
+        self.replace_range(0..self.len(), &normalized)
+    }
+
+    /// Replaces the given byte range of the current version with its content repeated `times`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or does not lie on char boundaries.
+    fn repeat_range(&mut self, range: Range<usize>, times: usize) {
+        let repeated = self.snapshot()[range.clone()].repeat(times);
+        self.replace_range(range, &repeated)
+    }
+
+    /// Replaces the given byte range of the current version with `replace_with`
+    /// producing a new version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or does not lie on char boundaries.
+    fn replace_range(&mut self, range: Range<usize>, replace_with: &str);
+
+    /// Reverses the characters of the current version producing a new version.
+    fn reverse(&mut self) {
+        let reversed = self.chars_rev().collect::<String>();
+        self.replace_range(0..self.len(), &reversed)
+    }
+
+    /// Replaces the character at byte `index` with `new_char` returning the replaced one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds or does not lie on a char boundary.
+    fn replace_char(&mut self, index: usize, new_char: char) -> char {
+        let old_char = self.snapshot()[index..]
+            .chars()
+            .next()
+            .expect("index should be less than the length of the string");
+        self.replace_range(
+            index..index + old_char.len_utf8(),
+            new_char.encode_utf8(&mut [0; 4]),
+        );
+
+        old_char
+    }
+
+    /// Truncates the current version to at most `max_bytes` bytes, rounding down
+    /// to a char boundary, producing a new version only if it was longer,
+    /// returning whether one was produced.
+    fn truncate_to_byte_limit(&mut self, max_bytes: usize) -> bool {
+        let len = self.len();
+        if len <= max_bytes {
+            return false;
+        }
+
+        let snapshot = self.snapshot();
+        let cut = (0..=max_bytes)
+            .rev()
+            .find(|&index| snapshot.is_char_boundary(index))
+            .expect("0 should always be a char boundary");
+        drop(snapshot);
+        self.replace_range(cut..len, "");
+
+        true
+    }
+
+    /// Swaps the character at byte `index` of the current version with the following one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` does not lie on a char boundary
+    /// or there are less than two characters starting at it.
+    fn transpose_chars(&mut self, index: usize) {
+        let snapshot = self.snapshot();
+        let mut characters = snapshot[index..].chars();
+        let (first, second) = match (characters.next(), characters.next()) {
+            (Some(first), Some(second)) => (first, second),
+            _ => panic!("there should be two characters starting at index {index}"),
+        };
+        drop(snapshot);
+        let transposed = [second, first].iter().collect::<String>();
+        self.replace_range(index..index + transposed.len(), &transposed)
+    }
+
+    /// Keeps only the first `new_len` bytes of the current version.
+    ///
+    /// A new version is produced even if the string is not longer than `new_len`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` is less than the length of the string
+    /// and does not lie on a char boundary.
+    fn truncate(&mut self, new_len: usize) {
+        let len = self.len();
+        self.replace_range(new_len.min(len)..len, "")
+    }
+
+    /// Keeps only the first `char_count` characters of the current version.
+    ///
+    /// A new version is produced even if the string is not longer than `char_count`.
+    fn truncate_chars(&mut self, char_count: usize) {
+        let len = self.len();
+        let cut = self.char_to_byte(char_count).unwrap_or(len);
+        self.replace_range(cut..len, "")
+    }
+
+    /// Applies the given operation by calling the corresponding method.
+    fn apply(&mut self, operation: Operation) {
+        match operation {
+            Operation::Push(character) => self.push(character),
+            Operation::PushStr(string) => self.push_str(&string),
+            Operation::Pop => {
+                self.pop();
+            }
+            Operation::Insert { index, string } => self.insert_str(index, &string),
+            Operation::Remove { index } => {
+                self.remove(index);
+            }
+            // `retain` is not object-safe thus its default implementation is inlined
+            Operation::Retain(filter) => {
+                let retained = self
+                    .snapshot()
+                    .chars()
+                    .filter(|&character| filter(character))
+                    .collect::<String>();
+                self.replace_range(0..self.len(), &retained)
+            }
+            Operation::Repeat(times) => self.repeat(times),
+            Operation::Clear => self.clear(),
+        }
+    }
+
+    /// Replaces the content of `other` with the content of this string's `version`
+    /// producing a new version of `other`.
+    fn copy_version_to(
+        &self,
+        version: usize,
+        other: &mut dyn PersistentString,
+    ) -> Result<(), VersionSwitchError> {
+        let snapshot = self.snapshot_at(version)?;
+        other.replace_range(0..other.len(), &snapshot);
+
+        Ok(())
+    }
+
+    /// Applies the operational transform components to the current version
+    /// producing a single new version.
+    ///
+    /// The retained and deleted lengths have to sum up exactly to the length of the string,
+    /// otherwise no version is produced.
+    fn apply_ot(&mut self, ops: &[OtOp]) -> Result<(), OtError> {
+        let result = ot::apply(&self.snapshot(), ops)?;
+        self.replace_range(0..self.len(), &result);
+
+        Ok(())
+    }
+
+    /// Returns the operational transform components which revert the application of `ops`
+    /// to the given version, restoring its content.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ops` do not [apply](Self::apply_ot) to the given version.
+    fn invert_ot(
+        &self,
+        ops: &[OtOp],
+        base_version: usize,
+    ) -> Result<Vec<OtOp>, VersionSwitchError> {
+        Ok(ot::invert(&self.snapshot_at(base_version)?, ops))
+    }
+
+    /// Replaces the content of the current version with the line-granular three-way merge
+    /// of the changes made from version `base` to versions `ours` and `theirs`,
+    /// returning whether there were conflicts.
+    ///
+    /// Conflicting changes are kept surrounded by diff3-style conflict markers
+    /// (`<<<<<<< ours`, `||||||| base`, `=======` and `>>>>>>> theirs`).
+    fn merge_with_markers(
+        &mut self,
+        base: usize,
+        ours: usize,
+        theirs: usize,
+    ) -> Result<bool, VersionSwitchError> {
+        let (merged, conflicted) = diff::merge_with_markers(
+            &self.snapshot_at(base)?,
+            &self.snapshot_at(ours)?,
+            &self.snapshot_at(theirs)?,
+        );
+        self.replace_range(0..self.len(), &merged);
+
+        Ok(conflicted)
+    }
+
+    /// Returns a wrapper of this string recording all operations applied via it.
+    fn record(&mut self) -> OperationRecorder<'_, Self>
+    where
+        Self: Sized,
+    {
+        OperationRecorder::new(self)
+    }
+
+    /// Starts a transaction whose edits all produce a single version.
+    fn edit(&mut self) -> EditTransaction<'_, Self>
+    where
+        Self: Sized,
+    {
+        EditTransaction::new(self)
+    }
+
     // Persistence management operations
 
+    /// Returns the number of the latest existing version.
+    fn latest_version(&self) -> usize;
+
+    /// Returns the number of bytes the backend stores specifically for the given version,
+    /// including the heap memory reserved for it.
+    fn version_internal_size(&self, version: usize) -> Result<usize, VersionSwitchError>;
+
+    /// Checks the integrity of the stored history,
+    /// returning an error if it has been corrupted.
+    fn validate_versions(&self) -> Result<(), VersionIntegrityError>;
+
+    /// Removes the versions whose content equals the one of their preceding version.
+    ///
+    /// The remaining versions get renumbered so that each one's number decreases
+    /// by the number of versions removed before it. If the current version gets removed,
+    /// the closest preceding remaining version (having the same content) becomes current.
+    fn collapse_no_op_versions(&mut self);
+
+    /// Makes the current version the new initial version keeping its content
+    /// and dropping all the preceding versions.
+    ///
+    /// Following versions are kept, each one's number decreasing by the former current one's.
+    fn rebase_onto_current(&mut self);
+
+    /// Splits the history at the given version into the one containing versions up to it
+    /// and the one starting with its content as the initial version.
+    ///
+    /// Each of the parts keeps the current version if it contains it
+    /// and otherwise has the version closest to it current.
+    fn split_history(&self, version: usize) -> Result<(Self, Self), VersionSwitchError>
+    where
+        Self: Sized;
+
     fn undo(&mut self) -> Result<(), UndoError>;
 
     fn undo_n(&mut self, times: usize) -> Result<(), UndoError> {
@@ -50,6 +1057,68 @@ pub trait PersistentString {
     }
 }
 
+// ensure that `PersistentString` stays object-safe
+const _: Option<&dyn PersistentString> = None;
+
+/// Returns the first byte offset at which the current versions of the strings differ
+/// or [`None`] if they are equal.
+pub fn first_difference(a: &dyn PersistentString, b: &dyn PersistentString) -> Option<usize> {
+    let (a, b) = (a.snapshot(), b.snapshot());
+
+    a.bytes()
+        .zip(b.bytes())
+        .position(|(a, b)| a != b)
+        .or_else(|| (a.len() != b.len()).then(|| a.len().min(b.len())))
+}
+
+/// A value which can be [appended](PersistentString::append) to a string.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Appendable<'a> {
+    /// A single character appended via [`push`](PersistentString::push).
+    Char(char),
+    /// A string appended via [`push_str`](PersistentString::push_str).
+    Str(Cow<'a, str>),
+}
+
+impl From<char> for Appendable<'_> {
+    fn from(character: char) -> Self {
+        Self::Char(character)
+    }
+}
+
+impl<'a> From<&'a str> for Appendable<'a> {
+    fn from(string: &'a str) -> Self {
+        Self::Str(Cow::Borrowed(string))
+    }
+}
+
+impl From<String> for Appendable<'_> {
+    fn from(string: String) -> Self {
+        Self::Str(Cow::Owned(string))
+    }
+}
+
+/// A style of line endings.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum NewlineStyle {
+    /// `\n`, used by Unix-like systems.
+    Lf,
+    /// `\r\n`, used by Windows.
+    CrLf,
+    /// `\r`, used by classic Mac OS.
+    Cr,
+}
+
+impl NewlineStyle {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::Cr => "\r",
+        }
+    }
+}
+
 /// An error which may happen when undoing an operation.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum UndoError {
@@ -61,3 +1130,29 @@ pub enum UndoError {
 pub enum RedoError {
     Terminal,
 }
+
+/// An error describing a corruption of version history.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum VersionIntegrityError {
+    /// The current version is greater than the latest one.
+    CurrentOutOfRange,
+    /// A stored delta cannot be applied to the version preceding it.
+    InvalidDelta,
+    /// A stored checkpoint does not match the version it corresponds to.
+    InvalidCheckpoint,
+}
+
+/// An error which may occur when accessing a string by an index.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum IndexError {
+    /// The index is greater than the length of the string.
+    OutOfBounds,
+    /// The index lies inside a multibyte character.
+    NotCharBoundary,
+}
+
+/// An error which may occur when accessing a specific version.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum VersionSwitchError {
+    NonExistent,
+}
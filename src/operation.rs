@@ -0,0 +1,48 @@
+use crate::PersistentString;
+
+/// A mutating operation of a [`PersistentString`]
+/// which may be applied via [`apply`](PersistentString::apply).
+#[derive(Clone, Debug)]
+pub enum Operation {
+    Push(char),
+    PushStr(String),
+    Pop,
+    Insert { index: usize, string: String },
+    Remove { index: usize },
+    Retain(fn(char) -> bool),
+    Repeat(usize),
+    Clear,
+}
+
+/// Wrapper of a [`PersistentString`] recording every [applied](Self::apply) [`Operation`]
+/// so that they can be replayed later.
+#[derive(Debug)]
+pub struct OperationRecorder<'a, S: PersistentString> {
+    string: &'a mut S,
+    operations: Vec<Operation>,
+}
+
+impl<'a, S: PersistentString> OperationRecorder<'a, S> {
+    pub(crate) fn new(string: &'a mut S) -> Self {
+        Self {
+            string,
+            operations: Vec::new(),
+        }
+    }
+
+    /// Applies the operation to the wrapped string recording it.
+    pub fn apply(&mut self, operation: Operation) {
+        self.operations.push(operation.clone());
+        self.string.apply(operation);
+    }
+
+    /// Returns the wrapped string.
+    pub fn string(&self) -> &S {
+        self.string
+    }
+
+    /// Returns all recorded operations in the order of their application.
+    pub fn into_ops(self) -> Vec<Operation> {
+        self.operations
+    }
+}
@@ -0,0 +1,71 @@
+/// An operational transform component applied in document order
+/// via [`apply_ot`](crate::PersistentString::apply_ot).
+///
+/// Lengths are measured in bytes.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum OtOp {
+    /// Keeps the given number of bytes.
+    Retain(usize),
+    /// Inserts the string.
+    Insert(String),
+    /// Removes the given number of bytes.
+    Delete(usize),
+}
+
+/// An error which may occur when applying operational transform components.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum OtError {
+    /// Retained and deleted bytes do not sum up to the length of the string.
+    LengthMismatch,
+    /// A component does not end on a char boundary.
+    NotCharBoundary,
+}
+
+/// Applies the components to `base` returning the resulting string.
+pub(crate) fn apply(base: &str, ops: &[OtOp]) -> Result<String, OtError> {
+    let mut result = String::with_capacity(base.len());
+    let mut position = 0usize;
+    for op in ops {
+        match op {
+            OtOp::Retain(len) | OtOp::Delete(len) => {
+                let end = position
+                    .checked_add(*len)
+                    .filter(|&end| end <= base.len())
+                    .ok_or(OtError::LengthMismatch)?;
+                if !base.is_char_boundary(end) {
+                    return Err(OtError::NotCharBoundary);
+                }
+                if let OtOp::Retain(_) = op {
+                    result.push_str(&base[position..end]);
+                }
+                position = end;
+            }
+            OtOp::Insert(string) => result.push_str(string),
+        }
+    }
+
+    if position == base.len() {
+        Ok(result)
+    } else {
+        Err(OtError::LengthMismatch)
+    }
+}
+
+/// Returns the components reverting the application of `ops` to `base`.
+pub(crate) fn invert(base: &str, ops: &[OtOp]) -> Vec<OtOp> {
+    let mut position = 0;
+    ops.iter()
+        .map(|op| match op {
+            OtOp::Retain(len) => {
+                position += len;
+                OtOp::Retain(*len)
+            }
+            OtOp::Insert(string) => OtOp::Delete(string.len()),
+            OtOp::Delete(len) => {
+                let deleted = base[position..position + len].to_string();
+                position += len;
+                OtOp::Insert(deleted)
+            }
+        })
+        .collect()
+}
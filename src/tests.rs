@@ -15,6 +15,81 @@ macro_rules! persistent_string_test_suite {
             test_push_with_many_undo,
             test_push_with_many_undo_and_redo,
             test_repeat,
+            test_nth_line,
+            test_byte_char_conversions,
+            test_replace_range,
+            test_truncate_chars,
+            test_replace_char,
+            test_chars_rev,
+            test_reverse,
+            test_snapshot_at,
+            test_common_prefix_len,
+            test_common_suffix_len,
+            test_changed_region,
+            test_matches_at,
+            test_is_empty_at,
+            test_char_operations,
+            test_apply,
+            test_record,
+            test_pop_through_pushes,
+            test_copy_version_to,
+            test_retain_range,
+            test_set_content,
+            test_diff_stats,
+            test_collapse_no_op_versions,
+            test_edit,
+            test_edit_rollback,
+            test_pop_while,
+            test_first_difference,
+            test_rebase_onto_current,
+            test_split_history,
+            test_version_hash,
+            test_version_hashes,
+            test_chaining,
+            test_push_bytes_lossy,
+            test_retain_if_changed,
+            test_byte_slice_at,
+            test_insert_subrange,
+            test_repeat_range,
+            test_normalize_newlines,
+            test_indent_lines,
+            test_cut_range,
+            test_for_each_version,
+            test_starts_with_at,
+            test_find_version,
+            test_truncate_to_byte_limit,
+            test_pad,
+            test_push_lines,
+            test_take,
+            test_insert_empty_str,
+            test_line_diff,
+            test_unified_diff,
+            test_char_at_line_col,
+            test_retain_stateful,
+            test_retain_nothing,
+            test_apply_ot,
+            test_invert_ot,
+            test_truncate,
+            test_transpose_chars,
+            test_line_count,
+            test_line_start_offset,
+            test_replace_lines,
+            test_validate_versions,
+            test_retain_bytes,
+            test_view,
+            test_merge_with_markers,
+            test_to_delta_log,
+            test_compare_versions,
+            test_try_insert_str,
+            test_snapshot_into_string,
+            test_repeat_empty,
+            test_append,
+            test_extract,
+            test_byte_and_char_len,
+            test_snapshot_owned,
+            test_retain_counting,
+            test_insert_str_nearest,
+            test_version_internal_size,
         );
     };
 }
@@ -190,3 +265,1774 @@ pub(crate) fn test_repeat<S: PersistentString>(factory: impl Fn() -> S) {
 
     assert_eq!(string.undo(), Err(UndoError::Terminal));
 }
+
+pub(crate) fn test_nth_line<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.nth_line(0), None);
+
+    string.push_str("first\nsecond");
+    string.push_str("\nthird\n");
+    assert_eq!(string.nth_line(0).as_deref(), Some("first"));
+    assert_eq!(string.nth_line(1).as_deref(), Some("second"));
+    assert_eq!(string.nth_line(2).as_deref(), Some("third"));
+    assert_eq!(string.nth_line(3).as_deref(), Some(""));
+    assert_eq!(string.nth_line(4), None);
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.nth_line(1).as_deref(), Some("second"));
+    assert_eq!(string.nth_line(2), None);
+}
+
+pub(crate) fn test_byte_char_conversions<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.byte_to_char(0), Some(0));
+    assert_eq!(string.char_to_byte(0), Some(0));
+    assert_eq!(string.byte_to_char(1), None);
+    assert_eq!(string.char_to_byte(1), None);
+
+    // byte lengths: 1, 2, 3, 4
+    string.push_str("aé€🙂");
+    assert_eq!(string.byte_to_char(0), Some(0));
+    assert_eq!(string.byte_to_char(1), Some(1));
+    assert_eq!(string.byte_to_char(2), None);
+    assert_eq!(string.byte_to_char(3), Some(2));
+    assert_eq!(string.byte_to_char(5), None);
+    assert_eq!(string.byte_to_char(6), Some(3));
+    assert_eq!(string.byte_to_char(10), Some(4));
+    assert_eq!(string.byte_to_char(11), None);
+
+    assert_eq!(string.char_to_byte(2), Some(3));
+    assert_eq!(string.char_to_byte(4), Some(10));
+    assert_eq!(string.char_to_byte(5), None);
+
+    for byte_index in [0, 1, 3, 6, 10] {
+        let char_index = string.byte_to_char(byte_index).unwrap();
+        assert_eq!(string.char_to_byte(char_index), Some(byte_index));
+    }
+}
+
+pub(crate) fn test_replace_range<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.replace_range(0..0, "foo");
+    assert_eq!(string.snapshot(), "foo");
+
+    string.replace_range(1..2, "ü€");
+    assert_eq!(string.snapshot(), "fü€o");
+
+    string.replace_range(3..6, "");
+    assert_eq!(string.snapshot(), "füo");
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "fü€o");
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "foo");
+
+    assert!(string.redo().is_ok());
+    assert_eq!(string.snapshot(), "fü€o");
+}
+
+pub(crate) fn test_truncate_chars<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("añ€🙂b");
+
+    string.truncate_chars(4);
+    assert_eq!(string.snapshot(), "añ€🙂");
+
+    string.truncate_chars(2);
+    assert_eq!(string.snapshot(), "añ");
+
+    string.truncate_chars(10);
+    assert_eq!(string.snapshot(), "añ");
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "añ");
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "añ€🙂");
+
+    string.truncate_chars(0);
+    assert!(string.snapshot().is_empty());
+}
+
+pub(crate) fn test_replace_char<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("abc");
+
+    assert_eq!(string.replace_char(1, '€'), 'b');
+    assert_eq!(string.snapshot(), "a€c");
+    assert_eq!(string.len(), 5);
+
+    assert_eq!(string.replace_char(1, 'x'), '€');
+    assert_eq!(string.snapshot(), "axc");
+    assert_eq!(string.len(), 3);
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "a€c");
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "abc");
+}
+
+pub(crate) fn test_chars_rev<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.chars_rev().next(), None);
+
+    string.push_str("añ€");
+    string.push_str("🙂z");
+    assert_eq!(
+        string.chars_rev().collect::<String>(),
+        string.snapshot().chars().rev().collect::<String>(),
+    );
+    assert_eq!(string.chars_rev().collect::<String>(), "z🙂€ña");
+}
+
+pub(crate) fn test_reverse<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.reverse();
+    assert!(string.snapshot().is_empty());
+
+    string.push_str("abc🙂");
+    string.reverse();
+    assert_eq!(string.snapshot(), "🙂cba");
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "abc🙂");
+}
+
+pub(crate) fn test_snapshot_at<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.latest_version(), 0);
+    assert_eq!(string.snapshot_at(0).as_deref(), Ok(""));
+    assert_eq!(string.snapshot_at(1), Err(VersionSwitchError::NonExistent));
+
+    string.push_str("foo");
+    string.push_str("bar");
+    string.repeat(2);
+    assert!(string.undo().is_ok());
+    assert_eq!(string.latest_version(), 3);
+    assert_eq!(string.snapshot_at(0).as_deref(), Ok(""));
+    assert_eq!(string.snapshot_at(1).as_deref(), Ok("foo"));
+    assert_eq!(string.snapshot_at(2).as_deref(), Ok("foobar"));
+    assert_eq!(string.snapshot_at(3).as_deref(), Ok("foobarfoobar"));
+    assert_eq!(string.snapshot_at(4), Err(VersionSwitchError::NonExistent));
+    assert_eq!(string.snapshot(), "foobar");
+
+    assert!(string.undo().is_ok());
+    string.push_str("baz");
+    assert_eq!(string.latest_version(), 2);
+    assert_eq!(string.snapshot_at(2).as_deref(), Ok("foobaz"));
+    assert_eq!(string.snapshot_at(3), Err(VersionSwitchError::NonExistent));
+}
+
+pub(crate) fn test_common_prefix_len<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("hello, world");
+    string.replace_range(7..8, "é");
+    // `é` and `è` share their first byte
+    string.replace_range(7..9, "è");
+
+    assert_eq!(string.common_prefix_len(1, 2), Ok(7));
+    assert_eq!(string.common_prefix_len(2, 1), Ok(7));
+    assert_eq!(string.common_prefix_len(1, 1), Ok(12));
+    assert_eq!(string.common_prefix_len(0, 1), Ok(0));
+    assert_eq!(string.common_prefix_len(2, 3), Ok(7));
+    assert_eq!(
+        string.common_prefix_len(1, 4),
+        Err(VersionSwitchError::NonExistent)
+    );
+}
+
+pub(crate) fn test_common_suffix_len<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("hello, world");
+    string.replace_range(4..5, "0");
+    string.replace_range(4..5, "ö");
+
+    let (prefix, suffix) = (
+        string.common_prefix_len(1, 2).unwrap(),
+        string.common_suffix_len(1, 2).unwrap(),
+    );
+    assert_eq!((prefix, suffix), (4, 7));
+    assert!(prefix + suffix <= string.snapshot_at(1).unwrap().len());
+
+    assert_eq!(string.common_suffix_len(2, 3), Ok(7));
+    assert_eq!(string.common_suffix_len(1, 3), Ok(7));
+    assert_eq!(string.common_suffix_len(3, 3), Ok(13));
+    assert_eq!(string.common_suffix_len(0, 3), Ok(0));
+    assert_eq!(
+        string.common_suffix_len(4, 3),
+        Err(VersionSwitchError::NonExistent)
+    );
+}
+
+pub(crate) fn test_changed_region<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("foobar");
+    string.replace_range(3..3, "xyz");
+    string.repeat(2);
+
+    assert_eq!(string.changed_region(1, 2), Ok((3, 3..3, 3..6)));
+    assert_eq!(string.changed_region(2, 1), Ok((3, 3..6, 3..3)));
+    assert_eq!(string.changed_region(1, 1), Ok((6, 6..6, 6..6)));
+    assert_eq!(string.changed_region(0, 1), Ok((0, 0..0, 0..6)));
+    // the suffix would overlap the prefix if not bounded
+    assert_eq!(string.changed_region(2, 3), Ok((9, 9..9, 9..18)));
+    assert_eq!(
+        string.changed_region(2, 4),
+        Err(VersionSwitchError::NonExistent)
+    );
+}
+
+pub(crate) fn test_matches_at<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert!(string.matches_at(0, ""));
+    assert!(!string.matches_at(0, "a"));
+    assert!(!string.matches_at(1, ""));
+
+    string.push_str("let x");
+    string.push_str(" = ñ;");
+    assert!(string.matches_at(0, "let"));
+    assert!(string.matches_at(4, "x = "));
+    assert!(string.matches_at(8, "ñ;"));
+    assert!(!string.matches_at(9, ";"));
+    assert!(string.matches_at(10, ";"));
+    assert!(!string.matches_at(10, ";;"));
+    assert!(string.matches_at(11, ""));
+    assert!(!string.matches_at(12, ""));
+}
+
+pub(crate) fn test_is_empty_at<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.is_empty_at(0), Ok(true));
+
+    string.push_str("a");
+    string.push_str("b");
+    string.repeat(0);
+    string.push_str("c");
+    assert!(string.undo().is_ok());
+
+    assert_eq!(string.is_empty_at(0), Ok(true));
+    assert_eq!(string.is_empty_at(1), Ok(false));
+    assert_eq!(string.is_empty_at(2), Ok(false));
+    assert_eq!(string.is_empty_at(3), Ok(true));
+    assert_eq!(string.is_empty_at(4), Ok(false));
+    assert_eq!(string.is_empty_at(5), Err(VersionSwitchError::NonExistent));
+}
+
+pub(crate) fn test_char_operations<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.pop(), None);
+    assert_eq!(string.latest_version(), 0);
+
+    string.push('a');
+    string.push('ß');
+    string.insert_str(1, "€c");
+    assert_eq!(string.snapshot(), "a€cß");
+
+    assert_eq!(string.remove(1), '€');
+    assert_eq!(string.snapshot(), "acß");
+
+    assert_eq!(string.pop(), Some('ß'));
+    assert_eq!(string.snapshot(), "ac");
+
+    string.push_str("ABCd");
+    string.retain(|character| !character.is_uppercase());
+    assert_eq!(string.snapshot(), "acd");
+
+    string.clear();
+    assert!(string.snapshot().is_empty());
+    assert_eq!(string.latest_version(), 8);
+
+    assert!(string.undo_n(3).is_ok());
+    assert_eq!(string.snapshot(), "ac");
+}
+
+pub(crate) fn test_apply<S: PersistentString>(factory: impl Fn() -> S) {
+    let operations = vec![
+        Operation::PushStr("Hello".to_string()),
+        Operation::Push('!'),
+        Operation::Insert {
+            index: 5,
+            string: ", world".to_string(),
+        },
+        Operation::Remove { index: 0 },
+        Operation::Retain(|character| character != 'o'),
+        Operation::Repeat(2),
+        Operation::Pop,
+    ];
+
+    let mut applied = factory();
+    for operation in operations {
+        applied.apply(operation);
+    }
+
+    let mut called = factory();
+    called.push_str("Hello");
+    called.push('!');
+    called.insert_str(5, ", world");
+    called.remove(0);
+    called.retain(|character| character != 'o');
+    called.repeat(2);
+    called.pop();
+
+    assert_eq!(applied.snapshot(), called.snapshot());
+    assert_eq!(applied.snapshot(), "ell, wrld!ell, wrld");
+    assert_eq!(applied.latest_version(), called.latest_version());
+
+    applied.apply(Operation::Clear);
+    assert!(applied.snapshot().is_empty());
+}
+
+pub(crate) fn test_record<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut original = factory();
+    original.push_str("unrecorded ");
+
+    let mut recorder = original.record();
+    recorder.apply(Operation::PushStr("macro".to_string()));
+    recorder.apply(Operation::Push('!'));
+    recorder.apply(Operation::Remove { index: 0 });
+    recorder.apply(Operation::Insert {
+        index: 0,
+        string: "U".to_string(),
+    });
+    assert_eq!(recorder.string().snapshot(), "Unrecorded macro!");
+    let operations = recorder.into_ops();
+    assert_eq!(operations.len(), 4);
+    assert_eq!(original.latest_version(), 5);
+
+    let mut replayed = factory();
+    replayed.push_str("unrecorded ");
+    for operation in operations {
+        replayed.apply(operation);
+    }
+    assert_eq!(replayed.snapshot(), original.snapshot());
+}
+
+pub(crate) fn test_pop_through_pushes<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("ab");
+    string.push('ç');
+    string.push_str("€🙂");
+
+    for (expected_pop, expected_snapshot) in [
+        ('🙂', "abç€"),
+        ('€', "abç"),
+        ('ç', "ab"),
+        ('b', "a"),
+        ('a', ""),
+    ] {
+        assert_eq!(string.pop(), Some(expected_pop));
+        assert_eq!(string.snapshot(), expected_snapshot);
+        assert_eq!(string.len(), expected_snapshot.len());
+    }
+    assert_eq!(string.pop(), None);
+    assert!(string.is_empty());
+    assert_eq!(string.latest_version(), 8);
+
+    assert!(string.undo_n(5).is_ok());
+    assert_eq!(string.snapshot(), "abç€🙂");
+}
+
+pub(crate) fn test_copy_version_to<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut source = factory();
+    source.push_str("first");
+    source.push_str(" draft");
+    source.push_str(" (final)");
+
+    let mut target = factory();
+    target.push_str("unrelated");
+    assert!(source.copy_version_to(2, &mut target).is_ok());
+    assert_eq!(target.snapshot(), "first draft");
+    assert_eq!(target.latest_version(), 2);
+
+    assert!(target.undo().is_ok());
+    assert_eq!(target.snapshot(), "unrelated");
+
+    assert_eq!(
+        source.copy_version_to(4, &mut target),
+        Err(VersionSwitchError::NonExistent)
+    );
+    assert_eq!(target.snapshot(), "unrelated");
+}
+
+pub(crate) fn test_retain_range<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("aeiou");
+    string.push_str("aeiöu");
+    string.push_str("aeiou");
+
+    let is_consonant = |character: char| !"aeiouö".contains(character);
+    string.retain_range(5..11, is_consonant);
+    assert_eq!(string.snapshot(), "aeiouaeiou");
+
+    string.retain_range(0..0, is_consonant);
+    assert_eq!(string.snapshot(), "aeiouaeiou");
+    assert_eq!(string.latest_version(), 5);
+
+    assert!(string.undo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "aeiouaeiöuaeiou");
+}
+
+pub(crate) fn test_set_content<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert!(!string.set_content(""));
+    assert_eq!(string.latest_version(), 0);
+
+    assert!(string.set_content("foo"));
+    assert!(!string.set_content("foo"));
+    assert_eq!(string.latest_version(), 1);
+
+    assert!(string.set_content("bar"));
+    assert_eq!(string.latest_version(), 2);
+    assert_eq!(string.snapshot(), "bar");
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "foo");
+}
+
+pub(crate) fn test_diff_stats<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("fn main() {\n}\n");
+    string.insert_str(12, "    println!(\"ü\");\n");
+    string.replace_range(11..12, "");
+
+    assert_eq!(
+        string.diff_stats(1, 2),
+        Ok(DiffStats {
+            inserted_chars: 19,
+            deleted_chars: 0,
+            inserted_lines: 1,
+            deleted_lines: 0,
+        })
+    );
+    assert_eq!(
+        string.diff_stats(2, 1),
+        Ok(DiffStats {
+            inserted_chars: 0,
+            deleted_chars: 19,
+            inserted_lines: 0,
+            deleted_lines: 1,
+        })
+    );
+    assert_eq!(
+        string.diff_stats(2, 3),
+        Ok(DiffStats {
+            inserted_chars: 0,
+            deleted_chars: 1,
+            inserted_lines: 0,
+            deleted_lines: 1,
+        })
+    );
+    assert_eq!(string.diff_stats(3, 3), Ok(DiffStats::default()));
+
+    string.set_content(&"a".repeat(1000));
+    string.replace_range(0..1, "b\n");
+    string.replace_range(1000..1001, "c");
+    assert_eq!(
+        string.diff_stats(4, 6),
+        Ok(DiffStats {
+            inserted_chars: 3,
+            deleted_chars: 2,
+            inserted_lines: 1,
+            deleted_lines: 0,
+        })
+    );
+    assert_eq!(
+        string.diff_stats(0, 7),
+        Err(VersionSwitchError::NonExistent)
+    );
+}
+
+pub(crate) fn test_collapse_no_op_versions<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.collapse_no_op_versions();
+    assert_eq!(string.latest_version(), 0);
+
+    string.push_str("");
+    string.push_str("foo");
+    string.repeat(1);
+    string.push_str("bar");
+    string.push_str("");
+    string.push_str("baz");
+    assert!(string.undo_n(2).is_ok());
+    assert_eq!(string.latest_version(), 6);
+
+    string.collapse_no_op_versions();
+    assert_eq!(string.latest_version(), 3);
+    assert_eq!(string.snapshot(), "foobar");
+    assert_eq!(string.snapshot_at(0).as_deref(), Ok(""));
+    assert_eq!(string.snapshot_at(1).as_deref(), Ok("foo"));
+    assert_eq!(string.snapshot_at(2).as_deref(), Ok("foobar"));
+    assert_eq!(string.snapshot_at(3).as_deref(), Ok("foobarbaz"));
+
+    assert!(string.redo().is_ok());
+    assert_eq!(string.snapshot(), "foobarbaz");
+    assert!(string.undo_n(3).is_ok());
+    assert_eq!(string.undo(), Err(UndoError::Terminal));
+}
+
+pub(crate) fn test_edit<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("world");
+
+    let mut transaction = string.edit();
+    transaction.push('!').insert_str(0, "hello ");
+    transaction.replace_range(0..1, "H");
+    assert_eq!(transaction.content(), "Hello world!");
+    transaction.commit();
+    assert_eq!(string.snapshot(), "Hello world!");
+    assert_eq!(string.latest_version(), 2);
+
+    {
+        let mut transaction = string.edit();
+        assert_eq!(transaction.pop(), Some('!'));
+        transaction.push('?');
+    }
+    assert_eq!(string.snapshot(), "Hello world?");
+    assert_eq!(string.latest_version(), 3);
+
+    {
+        let mut transaction = string.edit();
+        transaction.push('?');
+        assert_eq!(transaction.pop(), Some('?'));
+    }
+    assert_eq!(string.latest_version(), 3);
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "Hello world!");
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "world");
+}
+
+pub(crate) fn test_edit_rollback<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("foo");
+
+    let mut transaction = string.edit();
+    transaction.push_str("bar");
+    transaction.rollback();
+    assert_eq!(string.snapshot(), "foo");
+    assert_eq!(string.latest_version(), 1);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut transaction = string.edit();
+        transaction.push_str("bar");
+        transaction.insert_str(42, "baz");
+    }));
+    assert!(result.is_err());
+    assert_eq!(string.snapshot(), "foo");
+    assert_eq!(string.latest_version(), 1);
+}
+
+pub(crate) fn test_pop_while<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.pop_while(|_| true), "");
+    assert_eq!(string.latest_version(), 0);
+
+    string.push_str("let naïve");
+    string.push_str(" \t ");
+    assert_eq!(string.pop_while(char::is_whitespace), " \t ");
+    assert_eq!(string.snapshot(), "let naïve");
+
+    assert_eq!(
+        string.pop_while(|character| !character.is_whitespace()),
+        "naïve"
+    );
+    assert_eq!(string.snapshot(), "let ");
+    assert_eq!(string.latest_version(), 4);
+
+    assert_eq!(string.pop_while(|character| character == 'x'), "");
+    assert_eq!(string.latest_version(), 4);
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "let naïve");
+}
+
+pub(crate) fn test_first_difference<S: PersistentString>(factory: impl Fn() -> S) {
+    let (mut a, mut b) = (factory(), factory());
+    assert_eq!(first_difference(&a, &b), None);
+
+    a.push_str("hello world");
+    b.push_str("hello");
+    assert_eq!(first_difference(&a, &b), Some(5));
+    assert_eq!(first_difference(&b, &a), Some(5));
+
+    b.push_str("_world");
+    assert_eq!(first_difference(&a, &b), Some(5));
+
+    b.replace_char(5, ' ');
+    assert_eq!(first_difference(&a, &b), None);
+    assert_eq!(first_difference(&a, &CowPersistentString::new()), Some(0));
+}
+
+pub(crate) fn test_rebase_onto_current<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.rebase_onto_current();
+    assert!(string.snapshot().is_empty());
+    assert_eq!(string.latest_version(), 0);
+
+    string.push_str("foo");
+    string.push_str("bar");
+    string.push_str("baz");
+    string.push_str("qux");
+    assert!(string.undo_n(2).is_ok());
+
+    string.rebase_onto_current();
+    assert_eq!(string.snapshot(), "foobar");
+    assert_eq!(string.len(), 6);
+    assert_eq!(string.latest_version(), 2);
+    assert_eq!(string.snapshot_at(0).as_deref(), Ok("foobar"));
+    assert_eq!(string.undo(), Err(UndoError::Terminal));
+
+    assert!(string.redo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "foobarbazqux");
+    assert!(string.undo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "foobar");
+
+    string.repeat(2);
+    assert_eq!(string.snapshot(), "foobarfoobar");
+    assert_eq!(string.latest_version(), 1);
+    assert!(string.undo().is_ok());
+    assert_eq!(string.undo(), Err(UndoError::Terminal));
+    assert_eq!(string.snapshot(), "foobar");
+}
+
+pub(crate) fn test_split_history<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    for part in ["a", "b", "c", "d", "e"] {
+        string.push_str(part);
+    }
+    assert!(string.undo().is_ok());
+
+    let (past, future) = string.split_history(3).unwrap();
+    assert_eq!(past.latest_version(), 3);
+    assert_eq!(past.snapshot(), "abc");
+    for (version, expected) in ["", "a", "ab", "abc"].into_iter().enumerate() {
+        assert_eq!(past.snapshot_at(version).as_deref(), Ok(expected));
+    }
+
+    assert_eq!(future.latest_version(), 2);
+    assert_eq!(future.snapshot(), "abcd");
+    for (version, expected) in ["abc", "abcd", "abcde"].into_iter().enumerate() {
+        assert_eq!(future.snapshot_at(version).as_deref(), Ok(expected));
+    }
+
+    let (_, mut future) = string.split_history(5).unwrap();
+    assert_eq!(future.snapshot(), "abcde");
+    assert_eq!(future.undo(), Err(UndoError::Terminal));
+
+    assert!(string.split_history(6).is_err());
+    assert_eq!(string.snapshot(), "abcd");
+}
+
+pub(crate) fn test_version_hash<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("foo");
+    string.push_str("bar");
+    string.truncate_chars(3);
+
+    assert_eq!(string.version_hash(1), string.version_hash(3));
+    assert_ne!(string.version_hash(1), string.version_hash(2));
+    assert_ne!(string.version_hash(0), string.version_hash(1));
+    assert_eq!(string.version_hash(0), factory().version_hash(0));
+    assert_eq!(string.version_hash(4), Err(VersionSwitchError::NonExistent));
+}
+
+pub(crate) fn test_version_hashes<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.version_hashes().len(), 1);
+
+    string.push_str("draft");
+    string.push_str(" one");
+    string.set_content("rewrite");
+    string.set_content("draft one");
+    string.push('!');
+
+    let hashes = string.version_hashes();
+    assert_eq!(hashes.len(), 6);
+    let duplicates = (0..hashes.len())
+        .flat_map(|a| (a + 1..hashes.len()).map(move |b| (a, b)))
+        .filter(|&(a, b)| hashes[a] == hashes[b])
+        .collect::<Vec<_>>();
+    assert_eq!(duplicates, [(2, 4)]);
+}
+
+pub(crate) fn test_chaining<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string
+        .then_push_str("foo")
+        .then_push('-')
+        .then_repeat(2)
+        .then_insert_str(0, "<")
+        .then_replace_range(8..9, ">");
+    assert_eq!(string.snapshot(), "<foo-foo>");
+    assert_eq!(string.latest_version(), 5);
+
+    assert!(string.then_clear().is_empty());
+    assert_eq!(string.latest_version(), 6);
+}
+
+#[test]
+fn test_boxed_backends() {
+    let mut strings: Vec<Box<dyn PersistentString>> = vec![
+        Box::new(CowPersistentString::new()),
+        Box::new(DeltaPersistentString::new()),
+        Box::new(CheckpointedDeltaString::new()),
+    ];
+
+    for string in &mut strings {
+        string.push_str("foo");
+        string.push('!');
+        string.then_push_str("?");
+        assert!(string.undo().is_ok());
+    }
+    for string in &strings {
+        assert_eq!(string.snapshot(), "foo!");
+        assert_eq!(string.latest_version(), 3);
+    }
+}
+
+pub(crate) fn test_push_bytes_lossy<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_bytes_lossy("ok ".as_bytes());
+    string.push_bytes_lossy(b"bad \xF0\x90\x80 byte\xFF");
+    assert_eq!(string.snapshot(), "ok bad \u{FFFD} byte\u{FFFD}");
+    assert_eq!(string.latest_version(), 2);
+}
+
+pub(crate) fn test_retain_if_changed<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("a1b2");
+
+    assert!(!string.retain_if_changed(|_| true));
+    assert_eq!(string.latest_version(), 1);
+
+    string.retain(|_| true);
+    assert_eq!(string.latest_version(), 2);
+
+    assert!(string.retain_if_changed(|character| !character.is_ascii_digit()));
+    assert_eq!(string.snapshot(), "ab");
+    assert_eq!(string.latest_version(), 3);
+
+    assert!(!string.retain_if_changed(|character| !character.is_ascii_digit()));
+    assert_eq!(string.latest_version(), 3);
+}
+
+pub(crate) fn test_byte_slice_at<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("hello");
+    string.push_str(" world");
+    string.clear();
+    assert!(string.undo_n(2).is_ok());
+
+    assert_eq!(
+        string.byte_slice_at(2, 3..8).as_deref(),
+        Some(&b"lo wo"[..])
+    );
+    assert_eq!(string.byte_slice_at(2, 11..11).as_deref(), Some(&b""[..]));
+    assert_eq!(string.byte_slice_at(2, 3..12), None);
+    assert_eq!(string.byte_slice_at(3, 0..0).as_deref(), Some(&b""[..]));
+    assert_eq!(string.byte_slice_at(3, 0..1), None);
+    assert_eq!(string.byte_slice_at(4, 0..0), None);
+    assert_eq!(string.snapshot(), "hello");
+}
+
+pub(crate) fn test_insert_subrange<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut source = factory();
+    source.push_str("copy [this ünit] only");
+
+    let mut target = factory();
+    target.push_str("paste  here");
+    target.insert_subrange(6, &source, 6..16);
+    assert_eq!(target.snapshot(), "paste this ünit here");
+    assert_eq!(target.latest_version(), 2);
+
+    target.insert_subrange(0, &DeltaPersistentString::new(), 0..0);
+    assert_eq!(target.snapshot(), "paste this ünit here");
+    assert_eq!(target.latest_version(), 3);
+    assert_eq!(source.latest_version(), 1);
+}
+
+pub(crate) fn test_repeat_range<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("[abc]");
+
+    string.repeat_range(1..4, 4);
+    assert_eq!(string.snapshot(), "[abcabcabcabc]");
+    assert_eq!(string.len(), 14);
+
+    string.repeat_range(0..1, 0);
+    assert_eq!(string.snapshot(), "abcabcabcabc]");
+
+    string.repeat_range(12..12, 3);
+    assert_eq!(string.snapshot(), "abcabcabcabc]");
+    assert_eq!(string.latest_version(), 4);
+
+    assert!(string.undo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "[abcabcabcabc]");
+}
+
+pub(crate) fn test_normalize_newlines<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("a\nb\r\nc\rd\r\r\ne\n\r");
+
+    for (style, expected) in [
+        (NewlineStyle::Lf, "a\nb\nc\nd\n\ne\n\n"),
+        (NewlineStyle::CrLf, "a\r\nb\r\nc\r\nd\r\n\r\ne\r\n\r\n"),
+        (NewlineStyle::Cr, "a\rb\rc\rd\r\re\r\r"),
+    ] {
+        string.normalize_newlines(style);
+        assert_eq!(string.snapshot(), expected);
+        string.normalize_newlines(style);
+        assert_eq!(string.snapshot(), expected);
+    }
+    assert_eq!(string.latest_version(), 7);
+
+    string.normalize_newlines(NewlineStyle::Lf);
+    assert_eq!(string.snapshot_at(2), string.snapshot_at(8));
+}
+
+pub(crate) fn test_indent_lines<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.indent_lines("    ");
+    assert!(string.snapshot().is_empty());
+
+    string.push_str("fn main() {\n\nfoo();\n}");
+    string.indent_lines("    ");
+    assert_eq!(string.snapshot(), "    fn main() {\n\n    foo();\n    }");
+
+    string.dedent_lines("    ");
+    assert_eq!(string.snapshot_at(4), string.snapshot_at(2));
+
+    string.push('\n');
+    string.insert_str(13, "    ");
+    string.indent_lines("\t");
+    assert_eq!(string.snapshot(), "\tfn main() {\n\n\t    foo();\n\t}\n");
+
+    string.dedent_lines("\t    ");
+    assert_eq!(string.snapshot(), "\tfn main() {\n\nfoo();\n\t}\n");
+}
+
+pub(crate) fn test_cut_range<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("keep [cut ö] keep");
+
+    let mut cut = string.cut_range(5..13);
+    assert_eq!(string.snapshot(), "keep  keep");
+    assert_eq!(string.latest_version(), 2);
+    assert_eq!(cut.snapshot(), "[cut ö]");
+    assert_eq!(cut.latest_version(), 0);
+    assert_eq!(cut.undo(), Err(UndoError::Terminal));
+
+    cut.push('!');
+    assert!(cut.undo().is_ok());
+    assert_eq!(cut.snapshot(), "[cut ö]");
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "keep [cut ö] keep");
+}
+
+pub(crate) fn test_for_each_version<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("ab");
+    string.repeat(3);
+    string.pop();
+    assert!(string.undo().is_ok());
+
+    let mut visited = Vec::new();
+    let mut total_bytes = 0;
+    string.for_each_version(|version, content| {
+        visited.push(version);
+        total_bytes += content.len();
+        assert_eq!(string.snapshot_at(version).as_deref(), Ok(content));
+    });
+    assert_eq!(visited, [0, 1, 2, 3]);
+    assert_eq!(total_bytes, 2 + 6 + 5);
+}
+
+pub(crate) fn test_starts_with_at<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("draft");
+    string.insert_str(0, "# ");
+    string.push_str(" title");
+    string.remove(0);
+
+    assert_eq!(string.starts_with_at(0, ""), Ok(true));
+    assert_eq!(string.starts_with_at(1, "# "), Ok(false));
+    assert_eq!(
+        string.starts_with_at(5, "# "),
+        Err(VersionSwitchError::NonExistent)
+    );
+    let first_heading = (0..=string.latest_version())
+        .find(|&version| string.starts_with_at(version, "# ") == Ok(true));
+    assert_eq!(first_heading, Some(2));
+}
+
+pub(crate) fn test_find_version<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("abc");
+    string.push_str("def");
+    string.truncate_chars(2);
+    string.repeat(4);
+    assert!(string.undo_n(2).is_ok());
+
+    assert_eq!(string.find_version(|content| content.len() > 5), Some(2));
+    assert_eq!(string.find_version(|content| content.len() > 6), Some(4));
+    assert_eq!(string.find_version(str::is_empty), Some(0));
+    assert_eq!(string.find_version(|content| content.contains('x')), None);
+    assert_eq!(string.snapshot(), "abcdef");
+}
+
+pub(crate) fn test_truncate_to_byte_limit<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert!(!string.truncate_to_byte_limit(0));
+
+    // `€` occupies bytes 2..5
+    string.push_str("ab€cd");
+    assert!(!string.truncate_to_byte_limit(7));
+    assert!(!string.truncate_to_byte_limit(100));
+    assert_eq!(string.latest_version(), 1);
+
+    assert!(string.truncate_to_byte_limit(4));
+    assert_eq!(string.snapshot(), "ab");
+    assert_eq!(string.latest_version(), 2);
+
+    assert!(string.undo().is_ok());
+    assert!(string.truncate_to_byte_limit(5));
+    assert_eq!(string.snapshot(), "ab€");
+
+    assert!(string.truncate_to_byte_limit(0));
+    assert!(string.snapshot().is_empty());
+}
+
+pub(crate) fn test_pad<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("ñ1");
+
+    string.pad_start(4, '·');
+    assert_eq!(string.snapshot(), "··ñ1");
+    string.pad_end(7, '—');
+    assert_eq!(string.snapshot(), "··ñ1———");
+    assert_eq!(string.snapshot().chars().count(), 7);
+    assert_eq!(string.latest_version(), 3);
+
+    string.pad_start(7, '·');
+    string.pad_end(3, '—');
+    assert_eq!(string.latest_version(), 3);
+
+    assert!(string.undo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "ñ1");
+}
+
+pub(crate) fn test_push_lines<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_lines("");
+    assert_eq!(string.latest_version(), 0);
+
+    string.push_str("log:\n");
+    string.push_lines("first\nsecond\nthird");
+    assert_eq!(string.latest_version(), 4);
+    for (version, expected) in [
+        "log:\nfirst\n",
+        "log:\nfirst\nsecond\n",
+        "log:\nfirst\nsecond\nthird",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        assert_eq!(string.snapshot_at(version + 2).as_deref(), Ok(expected));
+    }
+
+    string.push_lines("\n\n");
+    assert_eq!(string.latest_version(), 6);
+}
+
+pub(crate) fn test_take<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("buffer");
+    string.push('!');
+
+    let taken = string.take();
+    assert!(string.is_empty());
+    assert_eq!(string.latest_version(), 3);
+    assert_eq!(taken.snapshot(), "buffer!");
+    assert_eq!(taken.latest_version(), 0);
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "buffer!");
+    assert!(factory().take().is_empty());
+}
+
+pub(crate) fn test_insert_empty_str<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.insert_str(0, "");
+    assert!(string.snapshot().is_empty());
+    assert_eq!(string.latest_version(), 1);
+
+    string.push_str("añb");
+    for index in [0, 1, 3, 4] {
+        string.insert_str(index, "");
+        assert_eq!(string.snapshot(), "añb");
+    }
+    assert_eq!(string.latest_version(), 6);
+    assert_eq!(string.snapshot_at(5), string.snapshot_at(6));
+
+    string.push('c');
+    assert_eq!(string.snapshot(), "añbc");
+    assert!(string.undo_n(5).is_ok());
+    assert_eq!(string.snapshot(), "añb");
+}
+
+pub(crate) fn test_line_diff<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("one\ntwo\nfour\n");
+    string.insert_str(8, "three\n");
+    string.replace_range(0..4, "");
+
+    let equal = |line: &str| LineDiffOp::Equal(line.to_string());
+    assert_eq!(
+        string.line_diff(1, 2),
+        Ok(vec![
+            equal("one\n"),
+            equal("two\n"),
+            LineDiffOp::Added("three\n".to_string()),
+            equal("four\n"),
+        ])
+    );
+    assert_eq!(
+        string.line_diff(2, 3),
+        Ok(vec![
+            LineDiffOp::Removed("one\n".to_string()),
+            equal("two\n"),
+            equal("three\n"),
+            equal("four\n"),
+        ])
+    );
+    assert_eq!(
+        string.line_diff(0, 3),
+        Ok(["two\n", "three\n", "four\n"]
+            .map(|line| LineDiffOp::Added(line.to_string()))
+            .to_vec())
+    );
+    assert_eq!(string.line_diff(3, 3).map(|diff| diff.len()), Ok(3));
+    assert_eq!(string.line_diff(3, 4), Err(VersionSwitchError::NonExistent));
+
+    string.set_content("x\n");
+    string.set_content("x\r\n");
+    assert_eq!(
+        string.line_diff(4, 5),
+        Ok(vec![
+            LineDiffOp::Removed("x\n".to_string()),
+            LineDiffOp::Added("x\r\n".to_string()),
+        ])
+    );
+    string.set_content("a");
+    string.set_content("a\n");
+    assert_eq!(
+        string.line_diff(6, 7),
+        Ok(vec![
+            LineDiffOp::Removed("a".to_string()),
+            LineDiffOp::Added("a\n".to_string()),
+        ])
+    );
+}
+
+pub(crate) fn test_unified_diff<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("1\n2\n3\n4\n5\n6\n7\n8\n9\n");
+    string.replace_range(2..3, "two");
+    string.replace_range(14..15, "");
+
+    assert_eq!(
+        string.unified_diff(1, 2, 1),
+        Ok("@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n".to_string())
+    );
+    assert_eq!(
+        string.unified_diff(1, 3, 1),
+        Ok(concat!(
+            "@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n",
+            "@@ -6,3 +6,3 @@\n 6\n-7\n+\n 8\n",
+        )
+        .to_string())
+    );
+    assert_eq!(
+        string.unified_diff(1, 3, 2),
+        Ok("@@ -1,9 +1,9 @@\n 1\n-2\n+two\n 3\n 4\n 5\n 6\n-7\n+\n 8\n 9\n".to_string())
+    );
+    assert_eq!(
+        string.unified_diff(0, 1, 0),
+        Ok("@@ -0,0 +1,9 @@\n+1\n+2\n+3\n+4\n+5\n+6\n+7\n+8\n+9\n".to_string())
+    );
+    assert_eq!(
+        string.unified_diff(2, 1, 0),
+        Ok("@@ -2 +2 @@\n-two\n+2\n".to_string())
+    );
+    assert_eq!(string.unified_diff(3, 3, 3), Ok(String::new()));
+
+    string.set_content("a\nb");
+    string.push('\n');
+    assert_eq!(
+        string.unified_diff(4, 5, 1),
+        Ok("@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n".to_string())
+    );
+    assert_eq!(
+        string.unified_diff(5, 4, 0),
+        Ok("@@ -2 +2 @@\n-b\n+b\n\\ No newline at end of file\n".to_string())
+    );
+}
+
+pub(crate) fn test_char_at_line_col<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.char_at_line_col(0, 0), None);
+
+    string.push_str("ab\nçd€\n\nz");
+    assert_eq!(string.char_at_line_col(0, 1), Some('b'));
+    assert_eq!(string.char_at_line_col(1, 0), Some('ç'));
+    assert_eq!(string.char_at_line_col(1, 2), Some('€'));
+    assert_eq!(string.char_at_line_col(1, 3), None);
+    assert_eq!(string.char_at_line_col(2, 0), None);
+    assert_eq!(string.char_at_line_col(3, 0), Some('z'));
+    assert_eq!(string.char_at_line_col(4, 0), None);
+}
+
+fn test_from_chunks<S: PersistentString>() {
+    let mut string = S::from_chunks(["foo", "bar", "", "baz"]);
+    assert_eq!(string.snapshot(), "foobarbaz");
+    assert_eq!(string.latest_version(), 0);
+    assert_eq!(string.undo(), Err(UndoError::Terminal));
+
+    string.truncate_chars(3);
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "foobarbaz");
+    assert!(S::from_chunks([]).is_empty());
+}
+
+#[test]
+fn test_from_chunks_all_backends() {
+    test_from_chunks::<CowPersistentString>();
+    test_from_chunks::<DeltaPersistentString>();
+    test_from_chunks::<CheckpointedDeltaString>();
+}
+
+fn test_with_content<S: PersistentString>() {
+    let mut string = S::with_content("hi");
+    assert_eq!(string.snapshot(), "hi");
+    assert_eq!(string.latest_version(), 0);
+
+    string.push('!');
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "hi");
+    assert!(S::with_content("").is_empty());
+}
+
+#[test]
+fn test_with_content_all_backends() {
+    test_with_content::<CowPersistentString>();
+    test_with_content::<DeltaPersistentString>();
+    test_with_content::<CheckpointedDeltaString>();
+}
+
+fn test_concat_all<S: PersistentString>() {
+    let parts = ["foo", "", "bar", " ", "baz"].map(|content| {
+        let mut part = S::with_content("?");
+        part.set_content(content);
+        part
+    });
+
+    let mut string = S::concat_all(parts);
+    assert_eq!(string.snapshot(), "foobar baz");
+    assert_eq!(string.latest_version(), 0);
+    assert_eq!(string.undo(), Err(UndoError::Terminal));
+    assert!(S::concat_all([]).is_empty());
+}
+
+#[test]
+fn test_concat_all_all_backends() {
+    test_concat_all::<CowPersistentString>();
+    test_concat_all::<DeltaPersistentString>();
+    test_concat_all::<CheckpointedDeltaString>();
+}
+
+fn test_from_version_contents<S: PersistentString>() {
+    let mut string = S::from_version_contents(vec![
+        "foo".to_string(),
+        "foo bar".to_string(),
+        "föo bar".to_string(),
+        "föo bar".to_string(),
+    ]);
+    assert_eq!(string.latest_version(), 3);
+    assert_eq!(string.snapshot(), "föo bar");
+
+    for expected in ["föo bar", "foo bar", "foo"] {
+        assert!(string.undo().is_ok());
+        assert_eq!(string.snapshot(), expected);
+    }
+    assert_eq!(string.undo(), Err(UndoError::Terminal));
+    assert!(string.redo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "föo bar");
+
+    let string = S::from_version_contents(Vec::new());
+    assert!(string.is_empty());
+    assert_eq!(string.latest_version(), 0);
+}
+
+#[test]
+fn test_from_version_contents_all_backends() {
+    test_from_version_contents::<CowPersistentString>();
+    test_from_version_contents::<DeltaPersistentString>();
+    test_from_version_contents::<CheckpointedDeltaString>();
+}
+
+pub(crate) fn test_retain_stateful<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("aébþcđ");
+
+    let mut visited = String::new();
+    let mut keep = false;
+    string.retain(|character| {
+        visited.push(character);
+        keep = !keep;
+        keep
+    });
+    assert_eq!(string.snapshot(), "abc");
+    assert_eq!(visited, "aébþcđ");
+
+    let mut count = 0;
+    string.retain_range(1..3, |_| {
+        count += 1;
+        count == 2
+    });
+    assert_eq!(string.snapshot(), "ac");
+    assert_eq!(count, 2);
+}
+
+pub(crate) fn test_retain_nothing<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("gone ñ");
+
+    string.retain(|_| false);
+    assert_eq!(string.len(), 0);
+    assert!(string.is_empty());
+    assert_eq!(string.snapshot(), "");
+    assert_eq!(string.latest_version(), 2);
+
+    string.retain(|_| false);
+    assert!(string.is_empty());
+    assert_eq!(string.latest_version(), 3);
+
+    assert!(string.undo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "gone ñ");
+    assert_eq!(string.len(), 7);
+}
+
+pub(crate) fn test_apply_ot<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("abcde");
+
+    let ops = [
+        OtOp::Retain(3),
+        OtOp::Insert("X".to_string()),
+        OtOp::Delete(2),
+    ];
+    assert_eq!(string.apply_ot(&ops), Ok(()));
+    assert_eq!(string.snapshot(), "abcX");
+    assert_eq!(string.latest_version(), 2);
+
+    let ops = [OtOp::Insert("é".to_string()), OtOp::Retain(4)];
+    assert_eq!(string.apply_ot(&ops), Ok(()));
+    assert_eq!(string.snapshot(), "éabcX");
+
+    for (ops, error) in [
+        (vec![OtOp::Retain(5)], OtError::LengthMismatch),
+        (vec![OtOp::Retain(7)], OtError::LengthMismatch),
+        (
+            vec![OtOp::Retain(2), OtOp::Retain(usize::MAX)],
+            OtError::LengthMismatch,
+        ),
+        (
+            vec![OtOp::Delete(1), OtOp::Retain(5)],
+            OtError::NotCharBoundary,
+        ),
+        (
+            vec![OtOp::Retain(2), OtOp::Delete(2), OtOp::Retain(3)],
+            OtError::LengthMismatch,
+        ),
+    ] {
+        assert_eq!(string.apply_ot(&ops), Err(error));
+    }
+    assert_eq!(string.latest_version(), 3);
+    assert_eq!(string.snapshot(), "éabcX");
+}
+
+pub(crate) fn test_invert_ot<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("hello wörld");
+
+    let ops = [
+        OtOp::Delete(1),
+        OtOp::Insert("H".to_string()),
+        OtOp::Retain(5),
+        OtOp::Delete(6),
+        OtOp::Insert("there".to_string()),
+    ];
+    assert_eq!(string.apply_ot(&ops), Ok(()));
+    assert_eq!(string.snapshot(), "Hello there");
+
+    let inverse = string.invert_ot(&ops, 1).unwrap();
+    assert_eq!(
+        inverse,
+        [
+            OtOp::Insert("h".to_string()),
+            OtOp::Delete(1),
+            OtOp::Retain(5),
+            OtOp::Insert("wörld".to_string()),
+            OtOp::Delete(5),
+        ]
+    );
+    assert_eq!(string.apply_ot(&inverse), Ok(()));
+    assert_eq!(string.snapshot(), "hello wörld");
+    assert_eq!(
+        string.invert_ot(&ops, 4),
+        Err(VersionSwitchError::NonExistent)
+    );
+}
+
+pub(crate) fn test_truncate<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.truncate(0);
+    assert_eq!(string.latest_version(), 1);
+
+    string.push_str("abç");
+    string.truncate(4);
+    assert_eq!(string.snapshot(), "abç");
+    assert_eq!(string.latest_version(), 3);
+    string.truncate(10);
+    assert_eq!(string.snapshot(), "abç");
+    assert_eq!(string.latest_version(), 4);
+
+    string.truncate(2);
+    assert_eq!(string.snapshot(), "ab");
+    assert_eq!(string.latest_version(), 5);
+
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "abç");
+    assert_eq!(string.snapshot_at(2), string.snapshot_at(4));
+}
+
+pub(crate) fn test_transpose_chars<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("ab");
+    string.transpose_chars(0);
+    assert_eq!(string.snapshot(), "ba");
+
+    string.push_str("ç🙂");
+    string.transpose_chars(2);
+    assert_eq!(string.snapshot(), "ba🙂ç");
+    string.transpose_chars(1);
+    assert_eq!(string.snapshot(), "b🙂aç");
+    assert_eq!(string.len(), 8);
+
+    assert!(string.undo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "baç🙂");
+}
+
+pub(crate) fn test_line_count<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.line_count(), 0);
+
+    string.push_str("one");
+    assert_eq!(string.line_count(), 1);
+    string.push_str("\ntwo");
+    assert_eq!(string.line_count(), 2);
+    string.push('\n');
+    assert_eq!(string.line_count(), 3);
+    assert_eq!(string.nth_line(2).as_deref(), Some(""));
+    string.push_str("\n\n");
+    assert_eq!(string.line_count(), 5);
+
+    assert!(string.undo_n(3).is_ok());
+    assert_eq!(string.line_count(), 1);
+}
+
+pub(crate) fn test_line_start_offset<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.line_start_offset(0), None);
+
+    string.push_str("ab\nçd\n\nz\n");
+    assert_eq!(string.line_start_offset(0), Some(0));
+    assert_eq!(string.line_start_offset(1), Some(3));
+    assert_eq!(string.line_start_offset(2), Some(7));
+    assert_eq!(string.line_start_offset(3), Some(8));
+    assert_eq!(string.line_start_offset(4), Some(10));
+    assert_eq!(string.line_start_offset(5), None);
+    for line in 0..5 {
+        let offset = string.line_start_offset(line).unwrap();
+        assert_eq!(
+            string.snapshot()[offset..].split('\n').next(),
+            string.nth_line(line).as_deref()
+        );
+    }
+}
+
+pub(crate) fn test_replace_lines<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("one\ntwo\nthree\nfour\nfive");
+
+    string.replace_lines(1..3, "2\n3");
+    assert_eq!(string.snapshot(), "one\n2\n3\nfour\nfive");
+    assert_eq!(string.latest_version(), 2);
+
+    string.replace_lines(1..3, "");
+    assert_eq!(string.snapshot(), "one\nfour\nfive");
+
+    string.replace_lines(1..1, "inserted\n");
+    assert_eq!(string.snapshot(), "one\ninserted\nfour\nfive");
+
+    string.replace_lines(2..4, "last");
+    assert_eq!(string.snapshot(), "one\ninserted\nlast");
+
+    string.replace_lines(3..3, "appended");
+    assert_eq!(string.snapshot(), "one\ninserted\nlast\nappended");
+
+    assert!(string.undo().is_ok());
+    assert!(string.undo().is_ok());
+    assert!(string.undo().is_ok());
+    assert!(string.undo().is_ok());
+    assert_eq!(string.snapshot(), "one\n2\n3\nfour\nfive");
+
+    string.set_content("one\ntwo\nthree");
+    string.replace_lines(1..3, "");
+    assert_eq!(string.snapshot(), "one");
+    assert_eq!(string.line_count(), 1);
+    string.replace_lines(0..1, "");
+    assert_eq!(string.snapshot(), "");
+    assert_eq!(string.nth_line(0), None);
+
+    string.replace_lines(0..0, "only");
+    assert_eq!(string.snapshot(), "only");
+    assert_eq!(string.nth_line(0).as_deref(), Some("only"));
+}
+
+pub(crate) fn test_validate_versions<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.validate_versions(), Ok(()));
+
+    string.push_str("foo bar");
+    string.replace_range(0..3, "baz");
+    string.repeat(2);
+    assert!(string.undo().is_ok());
+    assert_eq!(string.validate_versions(), Ok(()));
+
+    string.collapse_no_op_versions();
+    string.rebase_onto_current();
+    assert_eq!(string.validate_versions(), Ok(()));
+}
+
+pub(crate) fn test_retain_bytes<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("a\x07b\x07ç");
+
+    assert!(string.retain_bytes(|byte| byte != 0x07).is_ok());
+    assert_eq!(string.snapshot(), "abç");
+    assert_eq!(string.latest_version(), 2);
+
+    // `ç` is encoded as `0xC3 0xA7`
+    assert!(string.retain_bytes(|byte| byte != 0xA7).is_err());
+    assert_eq!(string.snapshot(), "abç");
+    assert_eq!(string.latest_version(), 2);
+}
+
+pub(crate) fn test_view<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("foo\nbar");
+    string.push('!');
+    assert!(string.undo().is_ok());
+
+    let view = string.view();
+    assert!(!view.is_empty());
+    assert_eq!(view.len(), 7);
+    assert_eq!(view.snapshot(), "foo\nbar");
+    assert_eq!(view.snapshot_at(2).unwrap(), "foo\nbar!");
+    assert_eq!(view.latest_version(), 2);
+    assert_eq!(view.line_count(), 2);
+    assert_eq!(view.nth_line(1).as_deref(), Some("bar"));
+    assert_eq!(view.find("bar"), Some(4));
+    assert_eq!(view.chars_rev().collect::<String>(), "rab\noof");
+    assert_eq!(view.version_hash(1), string.version_hash(1));
+}
+
+pub(crate) fn test_merge_with_markers<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("one\ntwo\nthree\nfour\nfive\n");
+    string.set_content("one\nTWO\nthree\nfour\nFIVE\n");
+    string.set_content("zero\none\n2\nthree\nfour\nfive\n");
+
+    assert_eq!(string.merge_with_markers(1, 2, 3), Ok(true));
+    assert_eq!(
+        string.snapshot(),
+        "zero\none\n\
+         <<<<<<< ours\nTWO\n||||||| base\ntwo\n=======\n2\n>>>>>>> theirs\n\
+         three\nfour\nFIVE\n"
+    );
+    assert_eq!(string.latest_version(), 4);
+
+    assert_eq!(string.merge_with_markers(1, 2, 1), Ok(false));
+    assert_eq!(string.snapshot(), "one\nTWO\nthree\nfour\nFIVE\n");
+
+    string.set_content("one\ntwo\nthree\nfour\nfive");
+    assert_eq!(string.merge_with_markers(1, 6, 3), Ok(false));
+    assert_eq!(string.snapshot(), "zero\none\n2\nthree\nfour\nfive");
+
+    string.set_content("a\r\nb\r\n");
+    string.set_content("A\r\nb\r\n");
+    assert_eq!(string.merge_with_markers(8, 9, 8), Ok(false));
+    assert_eq!(string.snapshot(), "A\r\nb\r\n");
+
+    string.set_content("\n");
+    assert_eq!(string.merge_with_markers(11, 11, 11), Ok(false));
+    assert_eq!(string.snapshot(), "\n");
+
+    string.set_content("a\nb");
+    string.set_content("a\nB");
+    string.set_content("a\nc");
+    assert_eq!(string.merge_with_markers(13, 14, 15), Ok(true));
+    assert_eq!(
+        string.snapshot(),
+        "a\n<<<<<<< ours\nB\n||||||| base\nb\n=======\nc\n>>>>>>> theirs\n"
+    );
+    assert_eq!(
+        string.merge_with_markers(1, 2, 17),
+        Err(VersionSwitchError::NonExistent)
+    );
+}
+
+pub(crate) fn test_to_delta_log<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert!(string.to_delta_log().is_empty());
+
+    string.push_str("foo bar");
+    string.replace_range(0..3, "baz");
+    string.push('!');
+    assert!(string.undo().is_ok());
+
+    let mut replayed = DeltaPersistentString::new();
+    for delta in string.to_delta_log() {
+        replayed.apply_delta(delta);
+    }
+    assert_eq!(replayed.snapshot(), string.snapshot());
+}
+
+pub(crate) fn test_compare_versions<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("b");
+    string.push_str("a");
+    string.replace_range(0..2, "a");
+
+    assert_eq!(string.compare_versions(1, 2), Ok(Ordering::Less));
+    assert_eq!(string.compare_versions(3, 1), Ok(Ordering::Less));
+    assert_eq!(string.compare_versions(2, 3), Ok(Ordering::Greater));
+    assert_eq!(string.compare_versions(0, 3), Ok(Ordering::Less));
+    assert_eq!(string.compare_versions(3, 3), Ok(Ordering::Equal));
+    assert_eq!(
+        string.compare_versions(3, 4),
+        Err(VersionSwitchError::NonExistent)
+    );
+
+    let mut versions = (0..=string.latest_version()).collect::<Vec<_>>();
+    versions.sort_by(|&a, &b| string.compare_versions(a, b).unwrap());
+    assert_eq!(versions, [0, 3, 1, 2]);
+}
+
+pub(crate) fn test_try_insert_str<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.try_insert_str(0, "çd"), Ok(()));
+    assert_eq!(string.try_insert_str(0, "ab"), Ok(()));
+    assert_eq!(string.try_insert_str(5, "e"), Ok(()));
+    assert_eq!(string.snapshot(), "abçde");
+    assert_eq!(string.latest_version(), 3);
+
+    assert_eq!(string.try_insert_str(7, "x"), Err(IndexError::OutOfBounds));
+    assert_eq!(
+        string.try_insert_str(3, "x"),
+        Err(IndexError::NotCharBoundary)
+    );
+    assert_eq!(string.snapshot(), "abçde");
+    assert_eq!(string.latest_version(), 3);
+}
+
+pub(crate) fn test_snapshot_into_string<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("foo ");
+    string.repeat(16);
+    assert_eq!(string.snapshot_len(), 64);
+
+    let mut buffer = String::new();
+    string.snapshot_into_string(&mut buffer);
+    assert_eq!(buffer, string.snapshot());
+    assert!(buffer.capacity() >= string.snapshot_len());
+
+    string.snapshot_into_string(&mut buffer);
+    assert_eq!(buffer.len(), 128);
+    assert!(buffer.starts_with(&*string.snapshot()));
+}
+
+pub(crate) fn test_repeat_empty<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.repeat(5);
+    assert!(string.is_empty());
+    assert_eq!(string.latest_version(), 1);
+    assert!(string.undo().is_ok());
+    assert_eq!(string.undo(), Err(UndoError::Terminal));
+
+    string.push_str("abc");
+    string.repeat(0);
+    assert!(string.is_empty());
+
+    string.repeat(usize::MAX);
+    assert!(string.is_empty());
+    assert_eq!(string.latest_version(), 3);
+    assert!(string.undo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "abc");
+}
+
+pub(crate) fn test_append<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.append('x');
+    assert_eq!(string.latest_version(), 1);
+    string.append("yz");
+    assert_eq!(string.latest_version(), 2);
+    string.append(String::from("ç"));
+    assert_eq!(string.latest_version(), 3);
+    string.append(Appendable::Char('!'));
+    assert_eq!(string.latest_version(), 4);
+    assert_eq!(string.snapshot(), "xyzç!");
+
+    assert!(string.undo_n(3).is_ok());
+    assert_eq!(string.snapshot(), "x");
+}
+
+pub(crate) fn test_extract<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("a1b2c3");
+
+    assert_eq!(
+        string.extract(|character| character.is_ascii_digit()),
+        "123"
+    );
+    assert_eq!(string.snapshot(), "abc");
+    assert_eq!(string.latest_version(), 2);
+
+    assert_eq!(string.extract(|character| character == 'ç'), "");
+    assert_eq!(string.snapshot(), "abc");
+    assert!(string.undo_n(2).is_ok());
+    assert_eq!(string.snapshot(), "a1b2c3");
+}
+
+pub(crate) fn test_byte_and_char_len<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!((string.byte_len(), string.char_len()), (0, 0));
+
+    string.push_str("aç€🙂");
+    assert_eq!(string.byte_len(), 10);
+    assert_eq!(string.byte_len(), string.len());
+    assert_eq!(string.char_len(), 4);
+}
+
+pub(crate) fn test_snapshot_owned<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.snapshot_owned(), "");
+
+    string.push_str("foo");
+    string.push('ç');
+    assert_eq!(string.snapshot_owned(), string.snapshot().into_owned());
+    assert_eq!(string.snapshot_owned(), "fooç");
+}
+
+/// Allocator delegating to [`Global`](std::alloc::Global) which counts the allocations made.
+#[cfg(feature = "allocator_api")]
+#[derive(Default)]
+pub(crate) struct CountingAllocator {
+    allocations: std::cell::Cell<usize>,
+}
+
+#[cfg(feature = "allocator_api")]
+impl CountingAllocator {
+    pub(crate) fn allocations(&self) -> usize {
+        self.allocations.get()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+unsafe impl std::alloc::Allocator for CountingAllocator {
+    fn allocate(
+        &self,
+        layout: std::alloc::Layout,
+    ) -> Result<std::ptr::NonNull<[u8]>, std::alloc::AllocError> {
+        self.allocations.set(self.allocations.get() + 1);
+        std::alloc::Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: std::ptr::NonNull<u8>, layout: std::alloc::Layout) {
+        unsafe { std::alloc::Global.deallocate(ptr, layout) }
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn test_new_in_all_backends() {
+    use std::alloc::Global;
+
+    let mut strings: Vec<Box<dyn PersistentString>> = vec![
+        Box::new(CowPersistentString::new_in(Global)),
+        Box::new(DeltaPersistentString::new_in(Global)),
+        Box::new(CheckpointedDeltaString::new_in(Global)),
+    ];
+
+    for string in &mut strings {
+        string.push_str("foo");
+        string.insert_str(0, "bar ");
+        assert!(string.undo().is_ok());
+        assert_eq!(string.snapshot(), "foo");
+        assert!(string.redo().is_ok());
+        assert_eq!(string.snapshot(), "bar foo");
+    }
+}
+
+pub(crate) fn test_retain_counting<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("persistent ström");
+
+    let is_consonant = |character: char| !"aeiouö".contains(character);
+    assert_eq!(string.retain_counting(is_consonant), 4);
+    assert_eq!(string.snapshot(), "prsstnt strm");
+    assert_eq!(string.retain_counting(is_consonant), 0);
+    assert_eq!(string.latest_version(), 3);
+}
+
+pub(crate) fn test_insert_str_nearest<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    assert_eq!(string.insert_str_nearest(3, "a🙂"), 0);
+
+    assert_eq!(string.insert_str_nearest(3, "b"), 1);
+    assert_eq!(string.snapshot(), "ab🙂");
+    assert_eq!(string.insert_str_nearest(2, "c"), 2);
+    assert_eq!(string.insert_str_nearest(42, "d"), 7);
+    assert_eq!(string.snapshot(), "abc🙂d");
+    assert_eq!(string.latest_version(), 4);
+}
+
+pub(crate) fn test_version_internal_size<S: PersistentString>(factory: impl Fn() -> S) {
+    let mut string = factory();
+    string.push_str("foo");
+    string.push('!');
+
+    for version in 0..=string.latest_version() {
+        assert!(string.version_internal_size(version).is_ok());
+    }
+    assert!(string.version_internal_size(1).unwrap() >= 3);
+    assert_eq!(
+        string.version_internal_size(3),
+        Err(VersionSwitchError::NonExistent)
+    );
+}
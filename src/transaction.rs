@@ -0,0 +1,104 @@
+use std::ops::Range;
+
+use crate::PersistentString;
+
+/// Guard accumulating edits of a [`PersistentString`] so that they all produce
+/// a single version once [committed](Self::commit) or dropped.
+///
+/// No version is produced if the resulting content equals the original one,
+/// if the transaction is [rolled back](Self::rollback)
+/// or if it is dropped while unwinding from a panic.
+#[must_use = "edits are committed once the transaction is dropped"]
+#[derive(Debug)]
+pub struct EditTransaction<'a, S: PersistentString> {
+    string: &'a mut S,
+    /// Content being edited.
+    working: String,
+    /// Whether the transaction has already been committed or rolled back.
+    finished: bool,
+}
+
+impl<'a, S: PersistentString> EditTransaction<'a, S> {
+    pub(crate) fn new(string: &'a mut S) -> Self {
+        let working = string.snapshot().into_owned();
+        Self {
+            string,
+            working,
+            finished: false,
+        }
+    }
+
+    /// Returns the content with all edits applied so far.
+    pub fn content(&self) -> &str {
+        &self.working
+    }
+
+    pub fn push(&mut self, character: char) -> &mut Self {
+        self.working.push(character);
+        self
+    }
+
+    pub fn push_str(&mut self, string: &str) -> &mut Self {
+        self.working.push_str(string);
+        self
+    }
+
+    pub fn insert(&mut self, index: usize, character: char) -> &mut Self {
+        self.working.insert(index, character);
+        self
+    }
+
+    pub fn insert_str(&mut self, index: usize, string: &str) -> &mut Self {
+        self.working.insert_str(index, string);
+        self
+    }
+
+    pub fn replace_range(&mut self, range: Range<usize>, replace_with: &str) -> &mut Self {
+        self.working.replace_range(range, replace_with);
+        self
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        self.working.pop()
+    }
+
+    pub fn remove(&mut self, index: usize) -> char {
+        self.working.remove(index)
+    }
+
+    pub fn truncate(&mut self, new_len: usize) -> &mut Self {
+        self.working.truncate(new_len);
+        self
+    }
+
+    pub fn clear(&mut self) -> &mut Self {
+        self.working.clear();
+        self
+    }
+
+    /// Commits the edits, this is equivalent to dropping the transaction.
+    pub fn commit(mut self) {
+        self.finish(true)
+    }
+
+    /// Discards the edits without producing a version.
+    pub fn rollback(mut self) {
+        self.finish(false)
+    }
+
+    fn finish(&mut self, commit: bool) {
+        if !self.finished {
+            self.finished = true;
+            if commit {
+                self.string.set_content(&self.working);
+            }
+        }
+    }
+}
+
+impl<S: PersistentString> Drop for EditTransaction<'_, S> {
+    fn drop(&mut self) {
+        // edits interrupted by a panic may be incomplete thus they should not be committed
+        self.finish(!std::thread::panicking());
+    }
+}
@@ -0,0 +1,78 @@
+use std::borrow::Cow;
+
+use crate::{CharsRev, PersistentString, VersionSwitchError};
+
+/// Immutable view of a [`PersistentString`] exposing only its non-mutating operations.
+///
+/// Passing a view instead of the string guarantees that no versions get created:
+///
+/// ```compile_fail
+/// use persistring::{CowPersistentString, PersistentString};
+///
+/// let mut string = CowPersistentString::new();
+/// string.view().push_str("foo");
+/// ```
+#[derive(Clone, Copy)]
+pub struct ReadOnlyView<'a> {
+    string: &'a dyn PersistentString,
+}
+
+impl<'a> ReadOnlyView<'a> {
+    pub fn new(string: &'a dyn PersistentString) -> Self {
+        Self { string }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.string.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.string.len()
+    }
+
+    /// See [`PersistentString::snapshot`].
+    pub fn snapshot(&self) -> Cow<'a, str> {
+        self.string.snapshot()
+    }
+
+    /// See [`PersistentString::snapshot_at`].
+    pub fn snapshot_at(&self, version: usize) -> Result<Cow<'a, str>, VersionSwitchError> {
+        self.string.snapshot_at(version)
+    }
+
+    /// See [`PersistentString::latest_version`].
+    pub fn latest_version(&self) -> usize {
+        self.string.latest_version()
+    }
+
+    /// See [`PersistentString::line_count`].
+    pub fn line_count(&self) -> usize {
+        self.string.line_count()
+    }
+
+    /// See [`PersistentString::nth_line`].
+    pub fn nth_line(&self, n: usize) -> Option<Cow<'a, str>> {
+        self.string.nth_line(n)
+    }
+
+    /// Returns the byte index of the first occurrence of `pattern` in the current version.
+    pub fn find(&self, pattern: &str) -> Option<usize> {
+        self.string.snapshot().find(pattern)
+    }
+
+    /// See [`PersistentString::chars_rev`].
+    pub fn chars_rev(&self) -> CharsRev<'a> {
+        self.string.chars_rev()
+    }
+
+    /// See [`PersistentString::version_hash`].
+    pub fn version_hash(&self, version: usize) -> Result<u64, VersionSwitchError> {
+        self.string.version_hash(version)
+    }
+}
+
+impl<'a> From<&'a dyn PersistentString> for ReadOnlyView<'a> {
+    fn from(string: &'a dyn PersistentString) -> Self {
+        Self::new(string)
+    }
+}